@@ -1,8 +1,10 @@
+use std::sync::Mutex;
+
 use bevy_asset::prelude::*;
 use bevy_asset::HandleId;
 use bevy_asset::LoadState;
 use bevy_ecs::prelude::*;
-use bevy_utils::HashSet;
+use bevy_utils::HashMap;
 
 use crate::Progress;
 
@@ -15,47 +17,128 @@ use crate::Progress;
 /// Note that failed/errored/unloaded assets are counted as completed.
 /// Otherwise, your game could get stuck on the loading screen.
 ///
+/// By default, an asset is considered ready as soon as the asset server reports
+/// it as loaded. If you need a custom definition of "ready" (say, a texture
+/// that must also be uploaded to the GPU), you can provide an additional check
+/// function, either for a specific asset (`.add_with_check`), or for all of
+/// them (`.set_ready_check`). The check is only performed after the asset
+/// server reports the asset as loaded, and is not performed for failed assets.
+///
 /// This resource is not added/removed when entering/exiting the load state.
 /// It is initialized with the app, so that it is available for you to add
 /// your asset handles before the load state becomes active.
 /// On exiting the load state, its value is simply cleared/reset.
+///
+/// Every asset counts as one unit of work by default. To make the progress
+/// reflect the amount of data rather than the number of files, you can give
+/// assets a weight (such as their expected size in bytes), using `.add_weighted`.
+///
+/// The asset tracking system is labeled [`Tracking`](crate::ProgressSystemLabel::Tracking),
+/// just like your own tracked systems, so that any system ordered after that
+/// label consistently sees the asset progress of the current frame. It also
+/// runs before your own tracked systems in the same stage.
 #[derive(Default, Resource)]
 pub struct AssetsLoading {
-    handles: HashSet<HandleId>,
+    // pending handles, with their weights
+    //
+    // in a Mutex, so that the tracking system can update it while also
+    // giving read-only access to the whole `World` to ready checks
+    handles: Mutex<HashMap<HandleId, u32>>,
     total: u32,
+    checks: HashMap<HandleId, Box<ReadyCheck>>,
+    global_check: Option<Box<ReadyCheck>>,
 }
 
+type ReadyCheck = dyn Fn(&HandleUntyped, &World) -> bool + Send + Sync;
+
 impl AssetsLoading {
     /// Add an asset to be tracked
     pub fn add<T: Into<HandleId>>(&mut self, handle: T) {
-        self.handles.insert(handle.into());
-        self.total += 1;
+        self.add_weighted(handle, 1);
+    }
+
+    /// Add an asset to be tracked, counting as `weight` units of work
+    ///
+    /// For example, use the expected file size, so that large assets
+    /// contribute more to the progress than small ones.
+    pub fn add_weighted<T: Into<HandleId>>(&mut self, handle: T, weight: u32) {
+        self.handles
+            .get_mut()
+            .unwrap()
+            .insert(handle.into(), weight);
+        self.total += weight;
+    }
+
+    /// Add an asset to be tracked, with a custom check to decide when it is ready
+    ///
+    /// After the asset server reports the asset as loaded, `check` is called
+    /// every frame, until it returns `true`. This overrides any check set with
+    /// `.set_ready_check`.
+    pub fn add_with_check<T: Into<HandleId>>(
+        &mut self,
+        handle: T,
+        check: impl Fn(&HandleUntyped, &World) -> bool + Send + Sync + 'static,
+    ) {
+        let id = handle.into();
+        self.checks.insert(id, Box::new(check));
+        self.add(id);
+    }
+
+    /// Set a custom check to decide when assets are ready
+    ///
+    /// Applies to all tracked assets that were not added with their own check
+    /// (using `.add_with_check`).
+    pub fn set_ready_check(
+        &mut self,
+        check: impl Fn(&HandleUntyped, &World) -> bool + Send + Sync + 'static,
+    ) {
+        self.global_check = Some(Box::new(check));
+    }
+
+    /// Add all the assets of a folder to be tracked
+    ///
+    /// Pass the handles returned by `AssetServer::load_folder`, after handling
+    /// its error. Every handle is tracked just like with `add`.
+    pub fn add_folder(&mut self, handles: &[HandleUntyped]) {
+        for handle in handles {
+            self.add(handle);
+        }
     }
 
     /// Have all assets finished loading?
     pub fn is_ready(&self) -> bool {
-        self.handles.is_empty()
+        self.handles.lock().unwrap().is_empty()
+    }
+
+    /// How many assets have not finished loading yet?
+    ///
+    /// This counts assets, regardless of their weight (see `.add_weighted`).
+    /// It is updated every frame by the tracking system, in the loading state.
+    pub fn remaining(&self) -> usize {
+        self.handles.lock().unwrap().len()
     }
 }
 
-pub(crate) fn assets_progress(
-    mut loading: ResMut<AssetsLoading>,
-    server: Res<AssetServer>,
-) -> Progress {
-    // TODO: avoid this temporary vec (HashSet::drain_filter is in Rust nightly)
-    let mut done = vec![];
-    for handle in loading.handles.iter() {
-        let loadstate = server.get_load_state(*handle);
-        if loadstate == LoadState::Loaded || loadstate == LoadState::Failed {
-            done.push(*handle);
+pub(crate) fn assets_progress(world: &World) -> Progress {
+    let loading = world.resource::<AssetsLoading>();
+    let server = world.resource::<AssetServer>();
+
+    let mut handles = loading.handles.lock().unwrap();
+    handles.retain(|handle, _| match server.get_load_state(*handle) {
+        LoadState::Failed => false,
+        LoadState::Loaded => {
+            let check = loading.checks.get(handle).or(loading.global_check.as_ref());
+            match check {
+                Some(check) => !check(&HandleUntyped::weak(*handle), world),
+                None => false,
+            }
         }
-    }
-    for handle in done {
-        loading.handles.remove(&handle);
-    }
+        _ => true,
+    });
 
+    let pending: u32 = handles.values().sum();
     Progress {
-        done: loading.total - loading.handles.len() as u32,
+        done: loading.total - pending,
         total: loading.total,
     }
 }
@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::{info, warn};
+
+use crate::Progress;
+use crate::ProgressCounter;
+
+/// Diagnostic features to help debug a misbehaving loading state
+///
+/// Enable them using [`ProgressPlugin::verbose`](crate::ProgressPlugin::verbose),
+/// or by setting the `diagnostics` field of the plugin. The default value
+/// enables everything.
+///
+/// This is meant for development. The diagnostics are configured per loading
+/// state: they only apply while in the state of the plugin they were set on.
+#[derive(Debug, Clone)]
+pub struct ProgressDiagnostics {
+    /// Log the overall progress every time it changes
+    pub log_changes: bool,
+    /// Warn if the overall progress has not changed for this long
+    ///
+    /// If `per_system` is enabled, the warning will list the systems that are
+    /// not ready yet.
+    pub stall_warning: Option<Duration>,
+    /// Record the progress reported by each individual tracked system
+    ///
+    /// See [`ProgressCounter::systems_progress`].
+    pub per_system: bool,
+    /// Record a sample of the overall progress every frame
+    ///
+    /// See [`ProgressCounter::history`].
+    pub record_history: bool,
+}
+
+impl Default for ProgressDiagnostics {
+    fn default() -> Self {
+        ProgressDiagnostics {
+            log_changes: true,
+            stall_warning: Some(Duration::from_secs(5)),
+            per_system: true,
+            record_history: true,
+        }
+    }
+}
+
+/// A sample of the overall progress at some point in time
+///
+/// See [`ProgressCounter::history`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressSample {
+    /// Time since entering the loading state
+    pub elapsed: Duration,
+    /// The visible progress (as returned by `ProgressCounter::progress`)
+    pub visible: Progress,
+    /// All progress, including hidden (as returned by `ProgressCounter::progress_complete`)
+    pub complete: Progress,
+}
+
+/// Keeps track of when progress last changed, for the stall warning
+#[derive(Default)]
+pub(crate) struct LastChange {
+    progress: Progress,
+    at: Option<Instant>,
+    warned: bool,
+}
+
+pub(crate) fn progress_diagnostics(
+    mut counter: ResMut<ProgressCounter>,
+    mut last: Local<LastChange>,
+) {
+    let diagnostics = match counter.diagnostics.clone() {
+        Some(diagnostics) => diagnostics,
+        None => return,
+    };
+    let visible = counter.progress();
+    let complete = counter.progress_complete();
+    let now = Instant::now();
+
+    // the counter is recreated every time the state is entered
+    let first_frame = counter.frame() == 1;
+
+    if first_frame || complete != last.progress {
+        if diagnostics.log_changes {
+            info!(
+                "Progress: {}/{} ({}/{} including hidden)",
+                visible.done, visible.total, complete.done, complete.total
+            );
+        }
+        *last = LastChange {
+            progress: complete,
+            at: Some(now),
+            warned: false,
+        };
+    }
+
+    if let (Some(threshold), Some(at)) = (diagnostics.stall_warning, last.at) {
+        if !last.warned && now - at >= threshold {
+            let waiting_on: Vec<_> = counter
+                .systems_progress()
+                .into_iter()
+                .filter(|(_, p)| !p.is_ready())
+                .map(|(name, p)| format!("{} ({}/{})", name, p.done, p.total))
+                .collect();
+            warn!(
+                "Progress has not changed for {:?}, stuck at {}/{}. Systems not ready: [{}]",
+                threshold,
+                complete.done,
+                complete.total,
+                waiting_on.join(", ")
+            );
+            last.warned = true;
+        }
+    }
+
+    if diagnostics.record_history {
+        let elapsed = counter.elapsed();
+        counter.history.push(ProgressSample {
+            elapsed,
+            visible,
+            complete,
+        });
+    }
+}
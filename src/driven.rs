@@ -0,0 +1,82 @@
+use bevy_ecs::prelude::*;
+
+use crate::ProgressCounter;
+
+/// Where a [`ProgressDrivenValue`] takes its value from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressValueSource {
+    /// The visible fraction (as returned by `ProgressCounter::progress`)
+    Visible,
+    /// The fraction of all work, including hidden (as returned by `ProgressCounter::progress_complete`)
+    Complete,
+    /// The visible fraction, never going backwards (as returned by `ProgressCounter::progress_monotonic`)
+    Monotonic,
+    /// The visible fraction, smoothed over time
+    ///
+    /// Every frame, the value moves this fraction (0.0..1.0) of the remaining
+    /// distance towards the actual progress. Note that this depends on the
+    /// frame rate.
+    Smoothed(f32),
+}
+
+/// Component to drive some value on an entity from the loading progress
+///
+/// Every frame, the current fraction (0.0..1.0) is computed from the
+/// [`ProgressCounter`], as configured by `source`, and passed to the setter
+/// along with the entity's component `C`. For example, to fade in the audio
+/// volume, or to animate a camera, as loading progresses.
+///
+/// To make it work, add the [`drive_progress_values::<C>`](drive_progress_values)
+/// system to your app, ordered after [`ProgressSystemLabel::Tracking`](crate::ProgressSystemLabel::Tracking).
+/// While there is no `ProgressCounter` (outside of the loading state), the
+/// setter is not called, so the last value stays in effect.
+#[derive(Component)]
+pub struct ProgressDrivenValue<C: Component> {
+    /// How to compute the value from the progress
+    pub source: ProgressValueSource,
+    setter: Box<dyn Fn(&mut C, f32) + Send + Sync>,
+    value: f32,
+}
+
+impl<C: Component> ProgressDrivenValue<C> {
+    /// Create a new driver, that calls `setter` with the value every frame
+    pub fn new(
+        source: ProgressValueSource,
+        setter: impl Fn(&mut C, f32) + Send + Sync + 'static,
+    ) -> Self {
+        ProgressDrivenValue {
+            source,
+            setter: Box::new(setter),
+            value: 0.0,
+        }
+    }
+
+    /// Get the value that was last passed to the setter
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+/// System to update all [`ProgressDrivenValue<C>`] components
+pub fn drive_progress_values<C: Component>(
+    counter: Option<Res<ProgressCounter>>,
+    mut q: Query<(&mut ProgressDrivenValue<C>, &mut C)>,
+) {
+    let counter = match counter {
+        Some(counter) => counter,
+        None => return,
+    };
+    let visible = counter.progress().fraction();
+    for (mut driver, mut target) in q.iter_mut() {
+        let value = match driver.source {
+            ProgressValueSource::Visible => visible,
+            ProgressValueSource::Complete => counter.progress_complete().fraction(),
+            ProgressValueSource::Monotonic => counter.progress_monotonic().fraction(),
+            ProgressValueSource::Smoothed(rate) => {
+                driver.value + (visible - driver.value) * rate.clamp(0.0, 1.0)
+            }
+        };
+        driver.value = value;
+        (driver.setter)(&mut *target, value);
+    }
+}
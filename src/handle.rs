@@ -0,0 +1,89 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// State shared between a [`ProgressCounter`](crate::ProgressCounter) and its handles
+#[derive(Default)]
+struct CompletionState {
+    /// Set once the outcome is known: whether progress completed
+    result: Option<bool>,
+    wakers: Vec<Waker>,
+}
+
+/// The counter's side, used to wake up anything waiting on a [`CompletionHandle`]
+#[derive(Default)]
+pub(crate) struct CompletionSignal(Arc<Mutex<CompletionState>>);
+
+impl CompletionSignal {
+    pub(crate) fn handle(&self) -> CompletionHandle {
+        CompletionHandle(self.0.clone())
+    }
+
+    /// Resolve all handles; only the first call has any effect
+    pub(crate) fn signal(&self, completed: bool) {
+        let wakers = {
+            let mut state = self.0.lock().unwrap();
+            if state.result.is_some() {
+                return;
+            }
+            state.result = Some(completed);
+            std::mem::take(&mut state.wakers)
+        };
+        // not holding the lock, in case a waker polls the future right away
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for CompletionSignal {
+    fn drop(&mut self) {
+        // the counter is gone without completing (say, because of a timeout)
+        self.signal(false);
+    }
+}
+
+/// Future that resolves when progress completes
+///
+/// Get one using [`ProgressCounter::wait_handle`](crate::ProgressCounter::wait_handle).
+///
+/// It resolves to `true` when progress completes (on the frame when the
+/// check-progress system detects it, before the state transition), or to
+/// `false` if the [`ProgressCounter`](crate::ProgressCounter) is removed
+/// without progress ever completing (for example, when the state is exited
+/// because of a timeout).
+///
+/// This does not depend on any particular async runtime: the waker is simply
+/// called from the thread running the check-progress system. Your executor
+/// must be running independently of that (like Bevy's task pools), because
+/// blocking the main schedule on this future would prevent progress from
+/// ever being checked.
+#[derive(Clone)]
+pub struct CompletionHandle(Arc<Mutex<CompletionState>>);
+
+impl CompletionHandle {
+    /// Check whether the handle has resolved, without waiting
+    ///
+    /// Returns `None` if the outcome is not known yet.
+    pub fn try_result(&self) -> Option<bool> {
+        self.0.lock().unwrap().result
+    }
+}
+
+impl Future for CompletionHandle {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<bool> {
+        let mut state = self.0.lock().unwrap();
+        match state.result {
+            Some(completed) => Poll::Ready(completed),
+            None => {
+                if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                    state.wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -1,53 +1,158 @@
+//! Backend using Bevy's built-in states (`State<S>`)
+//!
+//! This is the default, unless the `iyes_loopless` cargo feature is enabled.
+//! It is always available, though, so you can use it even then, by
+//! selecting [`ProgressBackend::Legacy`](crate::ProgressBackend::Legacy).
+//! In that case, use the [`ProgressSystem`] trait from this module, rather
+//! than the one from the crate root.
+
+use std::borrow::Cow;
+
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use bevy_ecs::schedule::StateData;
+use bevy_ecs::schedule::{ShouldRun, StateData};
 
 use crate::ApplyProgress;
-use crate::ProgressCounter;
-use crate::{ProgressPlugin, ProgressSystemLabel};
+use crate::TransitionConfig;
+use crate::{ProgressCompletion, ProgressCounter, ProgressPlugin, ProgressSystemLabel};
 
+/// Most used imports from this backend
 pub mod prelude {
+    pub use super::progress_at_least;
+    pub use super::progress_just_completed;
     pub use super::ProgressSystem;
 }
 
-impl<S: StateData> Plugin for ProgressPlugin<S> {
-    fn build(&self, app: &mut App) {
+impl<S: StateData> ProgressPlugin<S> {
+    pub(crate) fn build_legacy(&self, app: &mut App) {
+        self.build_common(app);
+        let transition_config = self.transition_config(&mut app.world);
+
+        #[cfg(feature = "assets")]
+        if self.track_assets {
+            app.init_resource::<crate::asset::AssetsLoading>();
+        }
+
+        #[cfg(not(feature = "assets"))]
+        if self.track_assets {
+            panic!("Enable the \"assets\" cargo feature to use assets tracking!");
+        }
+
+        #[cfg(feature = "scene")]
+        if self.track_scenes {
+            app.init_resource::<crate::scene::ScenesLoading>();
+        }
+
+        #[cfg(not(feature = "scene"))]
+        if self.track_scenes {
+            panic!("Enable the \"scene\" cargo feature to use scene tracking!");
+        }
+
+        for state in self.tracked_states() {
+            self.build_for_state_legacy(app, state, transition_config.clone());
+        }
+    }
+
+    fn build_for_state_legacy(
+        &self,
+        app: &mut App,
+        state: S,
+        transition_config: TransitionConfig<S>,
+    ) {
         app.add_system_set(
-            SystemSet::on_enter(self.state.clone()).with_system(crate::loadstate_enter),
+            SystemSet::on_enter(state.clone()).with_system(crate::loadstate_enter(
+                state.clone(),
+                self.expected_total,
+                self.observer_throttle,
+                self.diagnostics.clone(),
+            )),
         );
         app.add_system_set(
-            SystemSet::on_update(self.state.clone())
+            SystemSet::on_update(state.clone())
                 .with_system(
                     crate::next_frame
                         .at_start()
-                        .label(ProgressSystemLabel::Preparation),
+                        .label(ProgressSystemLabel::Preparation)
+                        .label(crate::label::Preparation),
                 )
                 .with_system(
-                    check_progress::<S>(self.next_state.clone())
+                    check_progress::<S>(transition_config)
                         .at_end()
-                        .label(ProgressSystemLabel::CheckProgress),
+                        .label(ProgressSystemLabel::CheckProgress)
+                        .label(crate::label::CheckProgress),
                 ),
         );
         app.add_system_set(
-            SystemSet::on_exit(self.state.clone()).with_system(crate::loadstate_exit),
+            SystemSet::on_exit(state.clone()).with_system(crate::loadstate_exit(
+                state.clone(),
+                self.accumulate,
+                self.soft_transition,
+            )),
         );
 
+        if self.soft_transition {
+            app.add_system_to_stage(
+                CoreStage::First,
+                crate::background_next_frame(state.clone()).at_start(),
+            );
+            app.add_system_to_stage(
+                CoreStage::Last,
+                crate::background_check(state.clone(), self.completion_epsilon),
+            );
+        }
+
+        if self.track_entities {
+            app.add_system_set(
+                SystemSet::on_update(state.clone())
+                    .with_system(crate::entity_progress.track_progress()),
+            );
+        }
+
+        if self.diagnostics.is_some() {
+            app.add_system_set(SystemSet::on_update(state.clone()).with_system(
+                crate::diagnostics::progress_diagnostics.after(ProgressSystemLabel::Tracking),
+            ));
+        }
+
+        #[cfg(feature = "bevy_diagnostic")]
+        if self.bevy_diagnostics {
+            app.add_system_set(
+                SystemSet::on_update(state.clone()).with_system(
+                    crate::metrics::update_metrics.after(ProgressSystemLabel::Tracking),
+                ),
+            );
+        }
+
         #[cfg(feature = "assets")]
         if self.track_assets {
-            app.init_resource::<crate::asset::AssetsLoading>();
             app.add_system_set(
-                SystemSet::on_update(self.state.clone())
-                    .with_system(crate::asset::assets_progress.track_progress()),
+                SystemSet::on_update(state.clone()).with_system(
+                    crate::asset::assets_progress
+                        .pipe(crate::apply_tracked_progress("AssetsLoading", None))
+                        .label(ProgressSystemLabel::Tracking)
+                        .label(crate::label::Tracking)
+                        .before(crate::UserTracking),
+                ),
             );
             app.add_system_set(
-                SystemSet::on_exit(self.state.clone())
-                    .with_system(crate::asset::assets_loading_reset),
+                SystemSet::on_exit(state.clone()).with_system(crate::asset::assets_loading_reset),
             );
         }
 
-        #[cfg(not(feature = "assets"))]
-        if self.track_assets {
-            panic!("Enable the \"assets\" cargo feature to use assets tracking!");
+        #[cfg(feature = "scene")]
+        if self.track_scenes {
+            app.add_system_set(
+                SystemSet::on_update(state.clone()).with_system(
+                    crate::scene::scenes_progress
+                        .pipe(crate::apply_tracked_progress("ScenesLoading", None))
+                        .label(ProgressSystemLabel::Tracking)
+                        .label(crate::label::Tracking)
+                        .before(crate::UserTracking),
+                ),
+            );
+            app.add_system_set(
+                SystemSet::on_exit(state).with_system(crate::scene::scenes_loading_reset),
+            );
         }
     }
 }
@@ -57,7 +162,20 @@ pub trait ProgressSystem<Params, T: ApplyProgress>: IntoSystem<(), T, Params> {
     /// Call this to add your system returning [`Progress`] to your [`App`]
     ///
     /// This adds the functionality for tracking the returned Progress.
+    ///
+    /// If the system runs while there is no [`ProgressCounter`](crate::ProgressCounter)
+    /// resource, the returned value is ignored (and a warning is logged, once).
     fn track_progress(self) -> bevy_ecs::schedule::SystemDescriptor;
+
+    /// Like `track_progress`, but also report a human-readable status text
+    ///
+    /// As long as the system's progress is not complete, the text is reported
+    /// in the [`ProgressStatus`](crate::ProgressStatus) resource, so it can be
+    /// shown in the UI (like "Generating terrain...").
+    fn track_progress_with_status(
+        self,
+        status: impl Into<Cow<'static, str>>,
+    ) -> bevy_ecs::schedule::SystemDescriptor;
 }
 
 impl<S, T, Params> ProgressSystem<Params, T> for S
@@ -66,21 +184,77 @@ where
     S: IntoSystem<(), T, Params>,
 {
     fn track_progress(self) -> bevy_ecs::schedule::SystemDescriptor {
-        self.pipe(|In(progress): In<T>, counter: Res<ProgressCounter>| {
-            progress.apply_progress(&*counter);
-        })
+        self.pipe(crate::apply_tracked_progress::<T>(
+            std::any::type_name::<S>(),
+            None,
+        ))
         .label(ProgressSystemLabel::Tracking)
+        .label(crate::label::Tracking)
+        .label(crate::UserTracking)
+    }
+
+    fn track_progress_with_status(
+        self,
+        status: impl Into<Cow<'static, str>>,
+    ) -> bevy_ecs::schedule::SystemDescriptor {
+        self.pipe(crate::apply_tracked_progress::<T>(
+            std::any::type_name::<S>(),
+            Some(status.into()),
+        ))
+        .label(ProgressSystemLabel::Tracking)
+        .label(crate::label::Tracking)
+        .label(crate::UserTracking)
+    }
+}
+
+/// Run criteria that is true once the visible progress reaches the given fraction
+///
+/// For example, `progress_at_least(0.5)` is true as soon as loading is half done
+/// (as returned by [`ProgressCounter::progress`](crate::ProgressCounter::progress)).
+///
+/// It is false whenever there is no [`ProgressCounter`](crate::ProgressCounter)
+/// (such as outside of the loading state), or no work has been reported yet
+/// (a total of `0`). You should order your system after
+/// [`ProgressSystemLabel::Tracking`], so that it sees the progress reported on
+/// the current frame.
+///
+/// Use it like `.with_run_criteria(progress_at_least(0.5))`.
+pub fn progress_at_least(
+    fraction: f32,
+) -> impl FnMut(Option<Res<ProgressCounter>>) -> ShouldRun + Clone {
+    move |counter| {
+        if crate::progress_reached(counter.as_deref(), fraction) {
+            ShouldRun::Yes
+        } else {
+            ShouldRun::No
+        }
     }
 }
 
-fn check_progress<S: StateData>(next_state: Option<S>) -> impl FnMut(&mut World) {
+/// Run criteria that is true only on the frame when progress in state `S` completes
+///
+/// See [`ProgressCompletion::is_transitioning`](crate::ProgressCompletion::is_transitioning)
+/// for details on exactly which frame that is.
+///
+/// Useful for running one-shot logic: `.with_run_criteria(progress_just_completed::<MyState>)`.
+pub fn progress_just_completed<S: StateData>(
+    completion: Option<Res<ProgressCompletion<S>>>,
+) -> ShouldRun {
+    if crate::just_completed(completion.as_deref()) {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// How many frames after the check-progress system the state transition happens
+const TRANSITION_DELAY: u64 = 0;
+
+fn check_progress<S: StateData>(mut config: TransitionConfig<S>) -> impl FnMut(&mut World) {
     move |world| {
-        let progress = world.resource::<ProgressCounter>().progress_complete();
-        if progress.is_ready() {
-            if let Some(next_state) = &next_state {
-                let mut state = world.resource_mut::<State<S>>();
-                state.set(next_state.clone()).ok();
-            }
+        if let Some(next_state) = config.next_state(world, TRANSITION_DELAY) {
+            let mut state = world.resource_mut::<State<S>>();
+            state.set(next_state).ok();
         }
     }
 }
@@ -12,7 +12,15 @@
 //! used with `iyes_loopless` to easily have many state types).
 //!
 //! Works with either legacy Bevy states (default) or `iyes_loopless` (via
-//! optional cargo feature).
+//! optional cargo feature). With the cargo feature enabled, the legacy backend
+//! is still available, and can be selected for each plugin, using
+//! `ProgressPlugin::with_backend`.
+//!
+//! **Note:** the cargo feature switches the default backend, and the
+//! `track_progress` method and run conditions exported in the crate root and
+//! the prelude, to `iyes_loopless`. Cargo features are unified across the
+//! whole build, so this also happens if some other crate enables it. Libraries
+//! should therefore always select their backend explicitly.
 //!
 //! To use this plugin, add one or more instances `ProgressPlugin` to your
 //! `App`, configuring for the relevant states.
@@ -41,40 +49,88 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
-use std::fmt::Debug;
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::fmt::{self, Debug};
 use std::hash::Hash;
-use std::ops::{Add, AddAssign};
-use std::sync::atomic::AtomicU32;
+use std::marker::PhantomData;
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Div, Mul};
+use std::str::FromStr;
 use std::sync::atomic::Ordering as MemOrdering;
+use std::sync::atomic::{AtomicU32, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_ecs::schedule::StateData;
+use bevy_ecs::system::BoxedSystem;
+use bevy_utils::tracing::warn;
+use bevy_utils::HashMap;
+
+use crate::diagnostics::{ProgressDiagnostics, ProgressSample};
+use crate::handle::{CompletionHandle, CompletionSignal};
+use crate::observer::{Observer, ObserverThrottle};
 
 #[cfg(feature = "assets")]
 mod asset;
-#[cfg(not(feature = "iyes_loopless"))]
-mod legacy;
+mod diagnostics;
+mod driven;
+mod handle;
+pub mod legacy;
 #[cfg(feature = "iyes_loopless")]
-mod loopless;
+pub mod loopless;
+#[cfg(feature = "bevy_diagnostic")]
+mod metrics;
+mod observer;
+#[cfg(feature = "scene")]
+mod scene;
+#[cfg(feature = "timer")]
+mod timer;
 
 /// Most used imports
 pub mod prelude {
     #[cfg(feature = "assets")]
     pub use crate::asset::AssetsLoading;
+    pub use crate::diagnostics::{ProgressDiagnostics, ProgressSample};
+    pub use crate::driven::{drive_progress_values, ProgressDrivenValue, ProgressValueSource};
+    pub use crate::handle::CompletionHandle;
     #[cfg(not(feature = "iyes_loopless"))]
     pub use crate::legacy::prelude::*;
     #[cfg(feature = "iyes_loopless")]
     pub use crate::loopless::prelude::*;
+    #[cfg(feature = "bevy_diagnostic")]
+    pub use crate::metrics::ProgressMetrics;
+    pub use crate::observer::ObserverThrottle;
+    #[cfg(feature = "scene")]
+    pub use crate::scene::ScenesLoading;
+    #[cfg(feature = "timer")]
+    pub use crate::timer::{TimerProgress, TimerProgressExt};
+    pub use crate::AccumulatedProgress;
+    pub use crate::ActiveProgressStates;
+    pub use crate::BackgroundProgress;
+    pub use crate::FreezeDisplayedProgress;
     pub use crate::HiddenProgress;
+    pub use crate::NextLoadingTarget;
     pub use crate::Progress;
+    pub use crate::ProgressBackend;
+    pub use crate::ProgressCompletion;
+    pub use crate::ProgressComponent;
     pub use crate::ProgressCounter;
+    pub use crate::ProgressFinalized;
     pub use crate::ProgressPlugin;
+    pub use crate::ProgressSource;
+    pub use crate::ProgressStatus;
+    pub use crate::ProgressSubset;
+    pub use crate::ReportedProgress;
+    pub use crate::SecondaryProgressComplete;
 }
 
 #[cfg(not(feature = "iyes_loopless"))]
-pub use crate::legacy::ProgressSystem;
+pub use crate::legacy::{progress_at_least, progress_just_completed, ProgressSystem};
 #[cfg(feature = "iyes_loopless")]
-pub use crate::loopless::ProgressSystem;
+pub use crate::loopless::{progress_at_least, progress_just_completed, ProgressSystem};
 
 /// Progress reported by a system
 ///
@@ -89,7 +145,12 @@ pub use crate::loopless::ProgressSystem;
 /// If you want your system to report some progress in a way that is counted separately
 /// and should not affect progress bars or other user-facing indicators, you can
 /// use [`HiddenProgress`] instead.
-#[derive(Debug, Clone, Copy, Default)]
+///
+/// This type can also be used as a component, if you want to store progress
+/// values on your entities (like for cooldowns or animations). Such components
+/// are *not* counted towards the [`ProgressCounter`]; if you want that, use
+/// [`ProgressComponent`] instead.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Progress {
     /// Units of work completed during this execution of the system
     pub done: u32,
@@ -119,9 +180,152 @@ impl From<Progress> for f64 {
 }
 
 impl Progress {
-    fn is_ready(self) -> bool {
+    /// Create a value representing fully completed work (`done == total`)
+    pub fn complete(total: u32) -> Self {
+        Progress { done: total, total }
+    }
+
+    /// Create a value representing work that has not started yet (`done == 0`)
+    pub fn none(total: u32) -> Self {
+        Progress { done: 0, total }
+    }
+
+    /// Create a value from a `bool`, counting as `weight` units of work
+    ///
+    /// Like converting from a `bool`, which counts as a single unit of work,
+    /// but lets a simple check contribute more to the progress.
+    pub fn flag(done: bool, weight: u32) -> Self {
+        Progress {
+            done: if done { weight } else { 0 },
+            total: weight,
+        }
+    }
+
+    /// Create a value for a loader with discrete steps: step `current` of `steps`
+    ///
+    /// The same as `Progress { done: current, total: steps }`.
+    pub fn step(current: u32, steps: u32) -> Self {
+        Progress {
+            done: current,
+            total: steps,
+        }
+    }
+
+    /// Get the value for the following step
+    ///
+    /// Does not go past the last step: if the work is already complete,
+    /// the value stays the same.
+    pub fn next(self) -> Self {
+        Progress {
+            done: self.done.saturating_add(1).min(self.total).max(self.done),
+            total: self.total,
+        }
+    }
+
+    /// Has all the work been completed? (is `done >= total`?)
+    pub fn is_ready(self) -> bool {
         self.done >= self.total
     }
+
+    fn is_ready_within(self, epsilon: u32) -> bool {
+        self.done.saturating_add(epsilon) >= self.total
+    }
+
+    /// Get the completed fraction, in the 0.0..1.0 range
+    ///
+    /// This is the same as converting into an `f32`, except that a total
+    /// of `0` is treated as fully complete (`1.0`), rather than `NaN`.
+    pub fn fraction(self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.into()
+        }
+    }
+
+    /// Get the completed fraction, rounded to the nearest whole percent
+    ///
+    /// See [`percent_rounded`](Self::percent_rounded).
+    pub fn fraction_rounded(self) -> f32 {
+        self.percent_rounded() as f32 / 100.0
+    }
+
+    /// Get the completed percentage, rounded down
+    ///
+    /// A value of `100` is only returned if the work is actually complete.
+    /// 49.6% is reported as `49`.
+    pub fn percent(self) -> u32 {
+        if self.total == 0 {
+            return 100;
+        }
+        (self.done as u64 * 100 / self.total as u64) as u32
+    }
+
+    /// Get the completed percentage, rounded to the nearest whole number
+    ///
+    /// Halfway values are rounded up, so 49.5% is reported as `50` and 0.5% as `1`.
+    /// This may report `100` before the work is actually complete; use
+    /// [`percent`](Self::percent) if that is not desirable.
+    pub fn percent_rounded(self) -> u32 {
+        if self.total == 0 {
+            return 100;
+        }
+        let total = self.total as u64;
+        ((self.done as u64 * 200 + total) / (total * 2)) as u32
+    }
+
+    /// Scale the completed fraction to an integer range, without using floats
+    ///
+    /// Returns `done / total * numerator`, rounded down. Useful for things like
+    /// pixel-exact progress bars: `progress.ratio_u32(bar_width)`.
+    ///
+    /// If `total` is `0`, the work is considered complete, and `numerator` is returned.
+    /// If `done` exceeds `total`, it is clamped, so the result never exceeds `numerator`.
+    pub fn ratio_u32(self, numerator: u32) -> u32 {
+        if self.total == 0 {
+            return numerator;
+        }
+        let done = self.done.min(self.total);
+        (done as u64 * numerator as u64 / self.total as u64) as u32
+    }
+
+    /// Combine two reports that measure the same underlying work
+    ///
+    /// Adding two `Progress` values (using `+`) sums up both `done` and `total`,
+    /// which is right when they represent independent pieces of work. If instead
+    /// they are two different measurements of the same work (possibly in different
+    /// units), use this method, so that the work is not counted twice.
+    ///
+    /// The result uses the larger of the two totals as the common denominator,
+    /// and its `done` is the average of both completed fractions, scaled to it.
+    /// A value with a total of `0` carries no information and is ignored.
+    pub fn merge_same_work(self, other: Progress) -> Progress {
+        if self.total == 0 {
+            return other;
+        }
+        if other.total == 0 {
+            return self;
+        }
+        let total = self.total.max(other.total) as u64;
+        let scaled_self = self.done.min(self.total) as u64 * total / self.total as u64;
+        let scaled_other = other.done.min(other.total) as u64 * total / other.total as u64;
+        Progress {
+            done: ((scaled_self + scaled_other) / 2) as u32,
+            total: total as u32,
+        }
+    }
+
+    /// Get the change in progress since a previous snapshot
+    ///
+    /// Useful for frame-over-frame telemetry. Both fields are computed with
+    /// saturating subtraction, so if a value went down since `previous` (like
+    /// when a system revises its total downward), its delta is `0`.
+    pub fn delta_since(self, previous: Progress) -> Progress {
+        Progress {
+            done: self.done.saturating_sub(previous.done),
+            total: self.total.saturating_sub(previous.total),
+        }
+    }
 }
 
 impl Add for Progress {
@@ -142,6 +346,91 @@ impl AddAssign for Progress {
     }
 }
 
+/// Scale both `done` and `total` up by a whole factor
+///
+/// The completed fraction stays the same. Saturates at `u32::MAX`.
+impl Mul<u32> for Progress {
+    type Output = Progress;
+
+    fn mul(self, rhs: u32) -> Self::Output {
+        Progress {
+            done: self.done.saturating_mul(rhs),
+            total: self.total.saturating_mul(rhs),
+        }
+    }
+}
+
+/// Scale both `done` and `total` down by a whole factor
+///
+/// Both are rounded down, so the completed fraction may change slightly.
+/// Panics if dividing by zero, just like integer division.
+impl Div<u32> for Progress {
+    type Output = Progress;
+
+    fn div(self, rhs: u32) -> Self::Output {
+        Progress {
+            done: self.done / rhs,
+            total: self.total / rhs,
+        }
+    }
+}
+
+/// Parse a value written as `"done/total"` (like `"3/10"`)
+///
+/// Whitespace around the numbers is allowed. Values where `done` is greater
+/// than `total` are rejected.
+impl FromStr for Progress {
+    type Err = ParseProgressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (done, total) = s.split_once('/').ok_or(ParseProgressError::MissingSlash)?;
+        let done = done
+            .trim()
+            .parse()
+            .map_err(ParseProgressError::InvalidNumber)?;
+        let total = total
+            .trim()
+            .parse()
+            .map_err(ParseProgressError::InvalidNumber)?;
+        if done > total {
+            return Err(ParseProgressError::DoneExceedsTotal { done, total });
+        }
+        Ok(Progress { done, total })
+    }
+}
+
+/// Error when parsing a [`Progress`] from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseProgressError {
+    /// The string is not in the `"done/total"` format
+    MissingSlash,
+    /// One of the parts is not a valid number
+    InvalidNumber(ParseIntError),
+    /// `done` is greater than `total`
+    DoneExceedsTotal {
+        /// The parsed `done` value
+        done: u32,
+        /// The parsed `total` value
+        total: u32,
+    },
+}
+
+impl fmt::Display for ParseProgressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseProgressError::MissingSlash => {
+                write!(f, "expected progress in the form \"done/total\"")
+            }
+            ParseProgressError::InvalidNumber(e) => write!(f, "invalid progress value: {}", e),
+            ParseProgressError::DoneExceedsTotal { done, total } => {
+                write!(f, "progress done ({}) exceeds total ({})", done, total)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseProgressError {}
+
 /// "Hidden" progress reported by a system.
 ///
 /// Works just like the regular [`Progress`], but will be accounted differently
@@ -151,16 +440,46 @@ impl AddAssign for Progress {
 /// state transition) as reported by the `progress_complete` method, but is not
 /// counted by the `progress` method. The intention is that it should not
 /// affect things like progress bars and other user-facing indicators.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct HiddenProgress(pub Progress);
 
+impl HiddenProgress {
+    /// Create a value representing fully completed work (see [`Progress::complete`])
+    pub fn complete(total: u32) -> Self {
+        HiddenProgress(Progress::complete(total))
+    }
+
+    /// Create a value representing work that has not started yet (see [`Progress::none`])
+    pub fn none(total: u32) -> Self {
+        HiddenProgress(Progress::none(total))
+    }
+}
+
+/// Component for progress tracked per-entity
+///
+/// For cases where each entity is responsible for loading itself, you can
+/// insert this component on your entities and update it as work is done, instead
+/// of writing a tracked system.
+///
+/// If enabled with [`ProgressPlugin::track_entities`], every frame, the values
+/// of all `ProgressComponent`s are summed up and added to [`ProgressCounter`],
+/// in addition to the progress reported by any tracked systems. The state
+/// transition will not happen until they are all ready.
+///
+/// When an entity is done, you can either leave the component in place (set to
+/// a ready value), or remove it. Note that removing it also removes its units
+/// of work from the total, so the progress bar may jump.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct ProgressComponent(pub Progress);
+
 /// Add this plugin to your app, to use this crate for the specified state.
 ///
 /// If you have multiple different states that need progress tracking,
 /// you can add the plugin for each one.
 ///
 /// If you want the optional assets tracking ("assets" cargo feature), enable
-/// it with `.track_assets()`.
+/// it with `.track_assets()`. Similarly, for the optional scene spawning
+/// tracking ("scene" cargo feature), use `.track_scenes()`.
 ///
 /// **Warning**: Progress tracking will only work in some stages!
 ///
@@ -193,6 +512,46 @@ pub struct ProgressPlugin<S: StateData> {
     pub next_state: Option<S>,
     /// Whether to enable the optional assets tracking feature
     pub track_assets: bool,
+    /// Whether to enable the optional scene spawning tracking feature
+    pub track_scenes: bool,
+    /// Whether to track [`ProgressComponent`]s
+    pub track_entities: bool,
+    /// Minimum time to stay in the loading state, even if all progress completes sooner
+    pub min_duration: Option<Duration>,
+    /// Maximum time to wait for progress to complete, before giving up
+    pub max_duration: Option<Duration>,
+    /// The state to transition to, if progress does not complete within `max_duration`
+    pub timeout_state: Option<S>,
+    /// How many units of work short of the total still count as complete
+    pub completion_epsilon: u32,
+    /// System to compute the next state, set using `continue_to_lazy`
+    ///
+    /// Wrapped in a `Mutex`, so that we can take it out when building the plugin.
+    next_state_system: Mutex<Option<BoxedSystem>>,
+    /// System to veto the transition, set using `on_complete_check`
+    complete_check_system: Mutex<Option<BoxedSystem<(), bool>>>,
+    /// Diagnostic features to enable, to help debug your loading state
+    pub diagnostics: Option<ProgressDiagnostics>,
+    /// Whether to report [`ProgressMetrics`](crate::prelude::ProgressMetrics) to `bevy_diagnostic`
+    pub bevy_diagnostics: bool,
+    /// The expected total (visible) units of work, to seed the counter with
+    pub expected_total: u32,
+    /// Part of the work that signals completion separately, before all work is done
+    pub secondary_completion: Option<ProgressSubset>,
+    /// Limits on how often progress observers are called
+    pub observer_throttle: ObserverThrottle,
+    /// Which implementation of states to use
+    pub backend: ProgressBackend,
+    /// Whether completed progress should carry over when the state is entered again
+    pub accumulate: bool,
+    /// Additional states in which progress is also tracked, just like in `state`
+    pub also_states: Vec<S>,
+    /// Whether some work must have been reported, before progress can be complete
+    pub require_work: bool,
+    /// Whether the progress counter should survive the transition out of the loading state
+    pub soft_transition: bool,
+    /// Adds to the [`ReportedProgress`] tally, set using `report_completion_to`
+    report_completion: Option<ReportCompletion>,
 }
 
 impl<S: StateData> ProgressPlugin<S> {
@@ -202,6 +561,25 @@ impl<S: StateData> ProgressPlugin<S> {
             state,
             next_state: None,
             track_assets: false,
+            track_scenes: false,
+            track_entities: false,
+            min_duration: None,
+            max_duration: None,
+            timeout_state: None,
+            completion_epsilon: 0,
+            next_state_system: Mutex::new(None),
+            complete_check_system: Mutex::new(None),
+            diagnostics: None,
+            bevy_diagnostics: false,
+            expected_total: 0,
+            secondary_completion: None,
+            observer_throttle: ObserverThrottle::default(),
+            backend: ProgressBackend::default(),
+            accumulate: false,
+            also_states: Vec::new(),
+            require_work: false,
+            soft_transition: false,
+            report_completion: None,
         }
     }
 
@@ -212,206 +590,3233 @@ impl<S: StateData> ProgressPlugin<S> {
         self
     }
 
+    /// Configure the [`ProgressPlugin`] to compute the next state when progress completes
+    ///
+    /// Use this if the state to go to depends on data that is only available at
+    /// that point, or is expensive to compute. The given system runs once, on
+    /// the frame when all progress in the loading state completes (after the
+    /// minimum duration, if any). It is a regular Bevy system, and it should
+    /// insert a [`NextLoadingTarget`] resource with the state to go to.
+    ///
+    /// The system runs inside the check-progress system (at the very end of the
+    /// [`ProgressSystemLabel::CheckProgress`] step), and its commands are applied
+    /// immediately. So, if it inserts the resource using `Commands`, the transition
+    /// happens on the same frame, just like with `continue_to`. If there is no
+    /// [`NextLoadingTarget`] yet, we keep checking for it every frame, so it can
+    /// also be inserted by some other system later.
+    ///
+    /// This overrides any state set with `continue_to`.
+    pub fn continue_to_lazy<Params>(self, system: impl IntoSystem<(), (), Params>) -> Self {
+        *self.next_state_system.lock().unwrap() = Some(Box::new(IntoSystem::into_system(system)));
+        self
+    }
+
+    /// Configure a check that has to pass before progress is considered complete
+    ///
+    /// Use this to hold the transition, even though all work is done. For
+    /// example, to wait for the user to click "Continue", or for a fade-out
+    /// animation to finish. The given system is a regular Bevy system,
+    /// returning `false` to defer the transition, or `true` to allow it.
+    ///
+    /// It runs inside the check-progress system, on every frame when all progress
+    /// is ready (after the minimum duration, if any), until it returns `true`.
+    /// Only then does progress count as completed, so while it returns `false`,
+    /// [`ProgressCompletion`], `continue_to_lazy`, etc. keep waiting. If progress
+    /// is no longer ready on some frame (say, because more work was added), it
+    /// is not run on that frame. The maximum duration (timeout) does not apply
+    /// while the transition is being held.
+    pub fn on_complete_check<Params>(self, system: impl IntoSystem<(), bool, Params>) -> Self {
+        *self.complete_check_system.lock().unwrap() =
+            Some(Box::new(IntoSystem::into_system(system)));
+        self
+    }
+
     #[cfg(feature = "assets")]
     /// Enable the optional assets tracking feature
     pub fn track_assets(mut self) -> Self {
         self.track_assets = true;
         self
     }
-}
 
-/// Label to control system execution order
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
-pub enum ProgressSystemLabel {
-    /// All systems tracking progress should run after this label
-    ///
-    /// The label is only needed for `at_start` exclusive systems.
-    /// Any parallel system or exclusive system at a different position
-    /// will run after it automatically.
-    Preparation,
-    /// Any system reading progress should ran after this label.
-    /// All systems wrapped in [`track`] automatically get this label.
-    Tracking,
-    /// All systems tracking progress should run before this label
+    /// Enable tracking of [`ProgressComponent`]s on entities
     ///
-    /// The label is only needed for `at_end` exclusive systems.
-    /// Any parallel system or exclusive system at a different position
-    /// will run before it automatically.
-    CheckProgress,
-}
+    /// Only enable this on one plugin per app: the counter is shared, so if
+    /// several plugins with this enabled are active at the same time, every
+    /// component would be counted more than once.
+    pub fn track_entities(mut self) -> Self {
+        self.track_entities = true;
+        self
+    }
 
-/// Resource for tracking overall progress
-///
-/// This resource is automatically created when entering a state that was
-/// configured using [`ProgressPlugin`], and removed when exiting it.
-#[derive(Default, Resource)]
-pub struct ProgressCounter {
-    // use atomics to track overall progress,
-    // so that we can avoid mut access in tracked systems,
-    // allowing them to run in parallel
-    done: AtomicU32,
-    total: AtomicU32,
-    done_hidden: AtomicU32,
-    total_hidden: AtomicU32,
-    persisted: Progress,
-    persisted_hidden: Progress,
-}
+    #[cfg(feature = "scene")]
+    /// Enable the optional scene spawning tracking feature
+    pub fn track_scenes(mut self) -> Self {
+        self.track_scenes = true;
+        self
+    }
 
-impl ProgressCounter {
-    /// Get the latest overall progress information
-    ///
-    /// This is the combined total of all systems.
+    /// Configure the timing policy of the loading state
     ///
-    /// To get correct information, make sure that you call this function only after
-    /// all your systems that track progress finished.
+    /// The loading state will be shown for at least `min`, even if all progress
+    /// completes sooner (useful to avoid a loading screen that just flickers
+    /// for a single frame). If progress completes after `min`, we transition
+    /// to the `continue_to` state immediately.
     ///
-    /// This does not include "hidden" progress. To get the full "real" total, use
-    /// `progress_complete`.
+    /// If progress has still not completed after `max`, loading is considered
+    /// to have failed, and we transition to `timeout_state` instead.
     ///
-    /// Use this method for progress bars and other things that indicate/report
-    /// progress information to the user.
-    pub fn progress(&self) -> Progress {
-        let total = self.total.load(MemOrdering::Acquire);
-        let done = self.done.load(MemOrdering::Acquire);
+    /// `min` must be less than `max`. This is checked when the plugin is added
+    /// to the app.
+    pub fn with_min_and_max_duration(
+        mut self,
+        min: Duration,
+        max: Duration,
+        timeout_state: S,
+    ) -> Self {
+        self.min_duration = Some(min);
+        self.max_duration = Some(max);
+        self.timeout_state = Some(timeout_state);
+        self
+    }
 
-        Progress { done, total }
+    /// Consider progress complete when it is within `epsilon` units of work of the total
+    ///
+    /// If your progress values are computed from floats or weights, rounding
+    /// can leave `done` just shy of `total`, so that loading would never complete.
+    /// This lets you tolerate that.
+    ///
+    /// The default is `0`, meaning that `done` must reach `total` exactly.
+    pub fn with_completion_epsilon(mut self, epsilon: u32) -> Self {
+        self.completion_epsilon = epsilon;
+        self
     }
 
-    /// Get the latest overall progress information
+    /// Enable all diagnostic features, to help debug a misbehaving loading state
     ///
-    /// This is the combined total of all systems.
+    /// This is meant for development. It enables logging of progress changes,
+    /// a warning when progress stalls, tracking of the progress reported by each
+    /// individual system, and recording of the progress history. See
+    /// [`ProgressDiagnostics`] for details, and if you want to enable only
+    /// some of them, set the `diagnostics` field instead.
     ///
-    /// To get correct information, make sure that you call this function only after
-    /// all your systems that track progress finished
+    /// This only applies to the state of this plugin. If not enabled, none of
+    /// the diagnostic systems are added for it.
+    pub fn verbose(mut self) -> Self {
+        self.diagnostics = Some(ProgressDiagnostics::default());
+        self
+    }
+
+    /// Accumulate progress across multiple entries into the loading state
     ///
-    /// This includes "hidden" progress. To get only the "visible" progress, use
-    /// `progress`.
+    /// Use this for states that are entered repeatedly, loading more content
+    /// each time (like streaming in more of the game world), if you want the
+    /// progress bar to reflect the cumulative total, rather than starting over
+    /// from zero every time.
     ///
-    /// This is the method to be used for things like state transitions, and other
-    /// use cases that must account for the "true" actual progress of the
-    /// registered systems.
-    pub fn progress_complete(&self) -> Progress {
-        let total =
-            self.total.load(MemOrdering::Acquire) + self.total_hidden.load(MemOrdering::Acquire);
-        let done =
-            self.done.load(MemOrdering::Acquire) + self.done_hidden.load(MemOrdering::Acquire);
+    /// The lifecycle is as follows:
+    ///  - When exiting the state, all work completed during this entry (visible
+    ///    and hidden separately) is stored in the [`AccumulatedProgress`] resource.
+    ///    Any work that was not completed by then is dropped, so that it cannot
+    ///    block future entries.
+    ///  - When entering the state again, the new [`ProgressCounter`] starts out
+    ///    with all of the accumulated work, as persisted (and complete) progress.
+    ///  - Your tracked systems then report the new work for this entry, on top.
+    ///    To add more work to the load, just report it from your systems (or use
+    ///    [`ProgressCounter::persist_progress`]), like usual.
+    ///  - To start over from zero, use [`AccumulatedProgress::reset`].
+    pub fn accumulate_across_entries(mut self) -> Self {
+        self.accumulate = true;
+        self
+    }
 
-        Progress { done, total }
+    /// Do not consider progress complete, until some work has actually been reported
+    ///
+    /// By default, if all tracked systems report a total of `0` (nothing to do),
+    /// progress is considered complete, and the transition happens right away.
+    /// That may be premature, if your systems only find out about their work
+    /// later (say, they are waiting for some external event). With this enabled,
+    /// the transition will only happen after some work has been reported at
+    /// least once (during the current entry into the loading state), and all
+    /// of that work has completed. Work carried over from previous entries
+    /// (see `accumulate_across_entries`) does not count as reported.
+    pub fn require_work(mut self) -> Self {
+        self.require_work = true;
+        self
     }
 
-    /// Add some amount of progress to the running total for the current frame.
+    /// Set the expected total (visible) units of work, every time the state is entered
     ///
-    /// In most cases you do not want to call this function yourself.
-    /// Let your systems return a [`Progress`] and wrap them in [`track`] instead.
-    pub fn manually_track(&self, progress: Progress) {
-        self.total.fetch_add(progress.total, MemOrdering::Release);
-        // use `min` to clamp in case a bad user provides `done > total`
-        self.done
-            .fetch_add(progress.done.min(progress.total), MemOrdering::Release);
+    /// See [`ProgressCounter::set_expected_total`].
+    pub fn with_expected_total(mut self, total: u32) -> Self {
+        self.expected_total = total;
+        self
     }
 
-    /// Add some amount of "hidden" progress to the running total for the current frame.
+    /// Signal when a part of the work completes, before all of it does
     ///
-    /// Hidden progress counts towards the true total (like for triggering the
-    /// state transition) as reported by the `progress_complete` method, but is not
-    /// counted by the `progress` method. The intention is that it should not
-    /// affect things like progress bars and other user-facing indicators.
+    /// For staged loading: say, show the main menu as soon as the essential
+    /// work is done, while other assets keep loading in the background. Report
+    /// the essential work as visible progress, and the rest as hidden progress
+    /// (or the other way round), and select which part to wait for with `subset`.
     ///
-    /// In most cases you do not want to call this function yourself.
-    /// Let your systems return a [`Progress`] and wrap them in [`track`] instead.
-    pub fn manually_track_hidden(&self, progress: HiddenProgress) {
-        self.total_hidden
-            .fetch_add(progress.0.total, MemOrdering::Release);
-        // use `min` to clamp in case a bad user provides `done > total`
-        self.done_hidden
-            .fetch_add(progress.0.done.min(progress.0.total), MemOrdering::Release);
+    /// There are then two thresholds, checked every frame by the check-progress
+    /// system:
+    ///  - when the selected part is complete, a [`SecondaryProgressComplete`]
+    ///    event is sent, once per entry into the loading state;
+    ///  - when all progress is complete, the regular transition happens, with all
+    ///    the usual settings (minimum duration, `on_complete_check`, etc.).
+    ///
+    /// Both thresholds use the completion epsilon (see
+    /// [`with_completion_epsilon`](Self::with_completion_epsilon)). The
+    /// secondary threshold ignores the other settings, but if all work completes
+    /// before the selected part alone was seen as complete, the event is still
+    /// sent first (on the same frame). Note that a part with no work at all
+    /// (a total of `0`) counts as complete.
+    ///
+    /// What to do with the event is up to you: for example, change a state of
+    /// a different type, or spawn the UI. If you want to transition out of the
+    /// loading state itself, see [`soft_transition`](Self::soft_transition).
+    pub fn secondary_completion(mut self, subset: ProgressSubset) -> Self {
+        self.secondary_completion = Some(subset);
+        self
     }
 
-    /// Persist progress for the rest of the current state
-    pub fn persist_progress(&mut self, progress: Progress) {
-        self.manually_track(progress);
-        self.persisted += progress;
+    /// Select which implementation of states to use
+    ///
+    /// By default, `iyes_loopless` is used if the cargo feature is enabled, and
+    /// Bevy's built-in states otherwise. Use this if you need the other one
+    /// (say, you are writing a library that should support either).
+    ///
+    /// Keep in mind that cargo features are unified across all crates in the
+    /// build: if any other crate enables `iyes_loopless` for this crate, the
+    /// default changes for you too (and so do the re-exports mentioned below).
+    /// If your code depends on a particular backend, select it explicitly.
+    ///
+    /// Note that the `track_progress` methods differ between backends, as they
+    /// return the descriptor types of the respective scheduling API. The ones
+    /// in the crate root and the prelude are for the default backend. For the
+    /// other, import [`legacy::ProgressSystem`] (or `loopless::ProgressSystem`)
+    /// instead. The same goes for `progress_at_least`.
+    pub fn with_backend(mut self, backend: ProgressBackend) -> Self {
+        self.backend = backend;
+        self
     }
 
-    /// Persist hidden progress for the rest of the current state
-    pub fn persist_progress_hidden(&mut self, progress: HiddenProgress) {
-        self.manually_track_hidden(progress);
-        self.persisted_hidden += progress.0;
+    /// Limit how often progress observers are called, every time the state is entered
+    ///
+    /// See [`ProgressCounter::set_observer_throttle`].
+    pub fn with_observer_throttle(mut self, throttle: ObserverThrottle) -> Self {
+        self.observer_throttle = throttle;
+        self
     }
-}
 
-/// Trait for all types that can be returned by systems to report progress
-pub trait ApplyProgress {
-    /// Account the value into the total progress for this frame
-    fn apply_progress(self, total: &ProgressCounter);
-}
+    /// Report progress metrics to Bevy's diagnostics
+    ///
+    /// This registers the diagnostics listed in [`ProgressMetrics`](crate::prelude::ProgressMetrics)
+    /// with the `Diagnostics` resource, and updates them every frame while in
+    /// the loading state, so they can be used with the standard Bevy diagnostics
+    /// tools, like `LogDiagnosticsPlugin`. You need to add Bevy's
+    /// `DiagnosticsPlugin` to your app yourself (it is part of `DefaultPlugins`).
+    ///
+    /// Not to be confused with [`ProgressPlugin::verbose`], which enables this
+    /// crate's own [`ProgressDiagnostics`].
+    #[cfg(feature = "bevy_diagnostic")]
+    pub fn with_diagnostics(mut self) -> Self {
+        self.bevy_diagnostics = true;
+        self
+    }
 
-impl ApplyProgress for Progress {
-    fn apply_progress(self, total: &ProgressCounter) {
-        total.manually_track(self);
+    /// Keep tracking progress in the background, after leaving the loading state
+    ///
+    /// Useful for streaming games, where the game can start while some
+    /// loading still continues. When progress completes, the transition to
+    /// the next state happens as usual, but the [`ProgressCounter`] (and
+    /// [`ProgressStatus`]) resources are not removed on exit. Tracked systems
+    /// that also run in other states (add them outside of the loading state)
+    /// can keep reporting their work into it.
+    ///
+    /// The lifecycle of the background counter is as follows:
+    ///  - On exiting the loading state, a [`BackgroundProgress`] resource is
+    ///    inserted, and the counter keeps being reset every frame (just like
+    ///    in the loading state).
+    ///  - As soon as all background work is complete (all progress, including
+    ///    hidden), both resources are removed. Note that this also happens if
+    ///    no tracked systems report anything.
+    ///  - If any tracked state (this one again, or another) is entered before
+    ///    that, the background counter is replaced by a new one for that state,
+    ///    and the `BackgroundProgress` resource is removed. There is only ever
+    ///    one `ProgressCounter`.
+    ///
+    /// Systems that only run in the loading state (including asset and scene
+    /// tracking) stop running on exit, and their last progress is not carried
+    /// into the background counter.
+    pub fn soft_transition(mut self) -> Self {
+        self.soft_transition = true;
+        self
     }
-}
 
-impl ApplyProgress for HiddenProgress {
-    fn apply_progress(self, total: &ProgressCounter) {
-        total.manually_track_hidden(self);
+    /// When progress completes, count it as `amount` units of work for a parent state
+    ///
+    /// Use this to keep a tally of completed sub-phases, if you split your
+    /// loading into several states (each with its own `ProgressPlugin`). Every
+    /// time progress in this state completes, `amount` units of completed work
+    /// are added for `parent`, in the [`ReportedProgress<P>`](ReportedProgress)
+    /// resource. The parent can be a state of any type, and does not need a
+    /// `ProgressPlugin` of its own, or to be active at all.
+    ///
+    /// Whenever `parent` is entered (if it has a `ProgressPlugin`), its
+    /// [`ProgressCounter`] starts out with the tally so far, as persisted (and
+    /// complete) progress, so that its progress bar reflects the completed
+    /// sub-phases. Its own tracked systems then report their work on top, as
+    /// usual. With `accumulate_across_entries`, the tally is not carried over
+    /// as part of the accumulated progress, so it is not counted twice.
+    ///
+    /// The tally is never cleared automatically; use [`ReportedProgress::reset`]
+    /// when starting over.
+    pub fn report_completion_to<P: StateData>(mut self, parent: P, amount: u32) -> Self {
+        self.report_completion = Some(Arc::new(move |world: &mut World| {
+            let mut reported = world.get_resource_or_insert_with(ReportedProgress::<P>::default);
+            *reported.map.entry(parent.clone()).or_default() += Progress::complete(amount);
+        }));
+        self
     }
-}
 
-impl<T: ApplyProgress> ApplyProgress for (T, T) {
-    fn apply_progress(self, total: &ProgressCounter) {
-        self.0.apply_progress(total);
-        self.1.apply_progress(total);
+    /// Also track progress in another state, in addition to the main one
+    ///
+    /// Use this if you have loading work that should be done (and tracked) in
+    /// more than one state. Everything set up by this plugin (the
+    /// [`ProgressCounter`], asset/scene/entity tracking, diagnostics, the
+    /// completion check and transition to the `continue_to` state, etc.) will
+    /// also apply in the given state.
+    ///
+    /// Each state gets its own counter: it is created fresh when entering any
+    /// of the states, and removed when exiting it. Your own tracked systems
+    /// still need to be added with run conditions for every state in which
+    /// they should run.
+    pub fn also_track_state(mut self, state: S) -> Self {
+        self.also_states.push(state);
+        self
+    }
+
+    /// All the states in which this plugin tracks progress
+    fn tracked_states(&self) -> impl Iterator<Item = S> + '_ {
+        std::iter::once(self.state.clone()).chain(self.also_states.iter().cloned())
+    }
+
+    /// Setup that is the same regardless of the backend
+    fn build_common(&self, app: &mut App) {
+        self.validate();
+
+        if self.accumulate {
+            app.init_resource::<AccumulatedProgress<S>>();
+        }
+
+        if self.secondary_completion.is_some() {
+            app.add_event::<SecondaryProgressComplete<S>>();
+        }
+
+        #[cfg(feature = "bevy_diagnostic")]
+        if self.bevy_diagnostics {
+            app.add_startup_system(crate::metrics::setup_metrics);
+        }
+
+        #[cfg(not(feature = "bevy_diagnostic"))]
+        if self.bevy_diagnostics {
+            panic!("Enable the \"bevy_diagnostic\" cargo feature to report progress to Bevy's diagnostics!");
+        }
+
+        app.init_resource::<ProgressFinalized>();
+        app.init_resource::<ActiveProgressStates>();
+
+        // there may be many plugins for the same state type, but we only want one of these
+        if !app.world.contains_resource::<ProgressCompletion<S>>() {
+            app.init_resource::<ProgressCompletion<S>>();
+            app.add_system_to_stage(CoreStage::First, completion_next_frame::<S>);
+        }
+    }
+
+    /// Panic if the plugin was misconfigured
+    fn validate(&self) {
+        if let (Some(min), Some(max)) = (self.min_duration, self.max_duration) {
+            if min >= max {
+                panic!(
+                    "ProgressPlugin for {:?}: min duration ({:?}) must be less than max duration ({:?})!",
+                    self.state, min, max
+                );
+            }
+        }
+    }
+
+    /// Extract the settings needed by the check-progress system
+    ///
+    /// This can only be done once, as it moves the `continue_to_lazy` and
+    /// `on_complete_check` systems out. The result can be cloned to be used for
+    /// every tracked state; they will share the same systems, which is fine,
+    /// as only one of them can be active at a time.
+    fn transition_config(&self, world: &mut World) -> TransitionConfig<S> {
+        let next_state_system = self
+            .next_state_system
+            .lock()
+            .unwrap()
+            .take()
+            .map(|mut system| {
+                system.initialize(world);
+                Arc::new(Mutex::new(system))
+            });
+        let complete_check_system =
+            self.complete_check_system
+                .lock()
+                .unwrap()
+                .take()
+                .map(|mut system| {
+                    system.initialize(world);
+                    Arc::new(Mutex::new(system))
+                });
+        TransitionConfig {
+            next_state: self.next_state.clone(),
+            min_duration: self.min_duration,
+            max_duration: self.max_duration,
+            timeout_state: self.timeout_state.clone(),
+            completion_epsilon: self.completion_epsilon,
+            require_work: self.require_work,
+            report_completion: self.report_completion.clone(),
+            secondary_completion: self.secondary_completion,
+            next_state_system,
+            complete_check_system,
+        }
     }
 }
 
-fn loadstate_enter(mut commands: Commands) {
-    commands.insert_resource(ProgressCounter::default());
+/// Add a [`ProgressPlugin`] for each of several loading states at once
+///
+/// Each `Loading -> Next` pair adds a plugin that tracks progress in the
+/// `Loading` state, and continues to the `Next` state when it completes. Both
+/// are variants of the given state type. This:
+///
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use iyes_progress::progress_states;
+/// # let mut app = App::default();
+/// progress_states!(app, MyState {
+///     Splash -> MainMenu,
+///     GameLoading -> InGame,
+/// });
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # enum MyState {
+/// #     Splash,
+/// #     MainMenu,
+/// #     GameLoading,
+/// #     InGame,
+/// # }
+/// ```
+///
+/// expands to exactly the same as:
+///
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use iyes_progress::ProgressPlugin;
+/// # let mut app = App::default();
+/// app.add_plugin(ProgressPlugin::new(MyState::Splash).continue_to(MyState::MainMenu));
+/// app.add_plugin(ProgressPlugin::new(MyState::GameLoading).continue_to(MyState::InGame));
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # enum MyState {
+/// #     Splash,
+/// #     MainMenu,
+/// #     GameLoading,
+/// #     InGame,
+/// # }
+/// ```
+///
+/// If you need to configure any of the plugins further, add that one manually.
+#[macro_export]
+macro_rules! progress_states {
+    ($app:expr, $state:ident { $($from:ident -> $to:ident),* $(,)? }) => {
+        $(
+            $app.add_plugin($crate::ProgressPlugin::new($state::$from).continue_to($state::$to));
+        )*
+    };
 }
 
-fn loadstate_exit(mut commands: Commands) {
-    commands.remove_resource::<ProgressCounter>();
+impl<S: StateData> Plugin for ProgressPlugin<S> {
+    fn build(&self, app: &mut App) {
+        match self.backend {
+            ProgressBackend::Legacy => self.build_legacy(app),
+            #[cfg(feature = "iyes_loopless")]
+            ProgressBackend::Loopless => self.build_loopless(app),
+        }
+    }
 }
 
-fn next_frame(world: &mut World) {
-    let counter = world.resource::<ProgressCounter>();
+/// Which implementation of states a [`ProgressPlugin`] uses
+///
+/// See [`ProgressPlugin::with_backend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressBackend {
+    /// Bevy's built-in states (`State<S>`), see [`legacy`]
+    #[cfg_attr(not(feature = "iyes_loopless"), default)]
+    Legacy,
+    /// `iyes_loopless` states, see `loopless`
+    #[cfg(feature = "iyes_loopless")]
+    #[default]
+    Loopless,
+}
 
-    counter
-        .done
-        .store(counter.persisted.done, MemOrdering::Release);
-    counter
-        .total
-        .store(counter.persisted.total, MemOrdering::Release);
+/// Adds to a [`ReportedProgress`] tally, see [`ProgressPlugin::report_completion_to`]
+type ReportCompletion = Arc<dyn Fn(&mut World) + Send + Sync>;
 
-    counter
-        .done_hidden
-        .store(counter.persisted_hidden.done, MemOrdering::Release);
-    counter
-        .total_hidden
-        .store(counter.persisted_hidden.total, MemOrdering::Release);
+/// The settings of a [`ProgressPlugin`] that decide when to transition, and where to
+#[derive(Clone)]
+struct TransitionConfig<S: StateData> {
+    next_state: Option<S>,
+    min_duration: Option<Duration>,
+    max_duration: Option<Duration>,
+    timeout_state: Option<S>,
+    completion_epsilon: u32,
+    require_work: bool,
+    report_completion: Option<ReportCompletion>,
+    secondary_completion: Option<ProgressSubset>,
+    next_state_system: Option<Arc<Mutex<BoxedSystem>>>,
+    complete_check_system: Option<Arc<Mutex<BoxedSystem<(), bool>>>>,
 }
 
-/// Dummy system to count for a number of frames
-///
-/// May be useful for testing/debug/workaround purposes.
-pub fn dummy_system_wait_frames<const N: u32>(mut count: Local<u32>) -> HiddenProgress {
-    if *count <= N {
-        *count += 1;
+impl<S: StateData> TransitionConfig<S> {
+    /// Figure out which state (if any) we should transition to this frame
+    ///
+    /// Used by both backends, which only differ in how they perform the transition.
+    /// `transition_delay` is how many frames later the backend will actually
+    /// perform the transition, which is needed for [`ProgressCompletion`].
+    fn next_state(&mut self, world: &mut World, transition_delay: u64) -> Option<S> {
+        self.check_secondary(world);
+        world.resource_mut::<ProgressFinalized>().finalized = true;
+        let counter = world.resource::<ProgressCounter>();
+        let snapshot = (counter.progress(), counter.progress_hidden());
+        world
+            .resource_mut::<ActiveProgressStates>()
+            .update::<S>(snapshot);
+
+        let mut counter = world.resource_mut::<ProgressCounter>();
+        counter.notify_observers(self.completion_epsilon);
+        let elapsed = counter.elapsed();
+        let progress = counter.progress_complete();
+        // work carried over from previous entries does not count,
+        // only work reported during this entry
+        if progress.total > counter.seeded_total {
+            counter.seen_work = true;
+        }
+        let ready = progress.is_ready_within(self.completion_epsilon)
+            && (counter.seen_work || !self.require_work);
+        if ready {
+            if self.min_duration.is_none_or(|min| elapsed >= min) {
+                let just_completed = !counter.completed;
+                if just_completed {
+                    if let Some(system) = &self.complete_check_system {
+                        let mut system = system.lock().unwrap();
+                        let allowed = system.run((), world);
+                        system.apply_buffers(world);
+                        if !allowed {
+                            return None;
+                        }
+                    }
+                    let mut counter = world.resource_mut::<ProgressCounter>();
+                    counter.completed = true;
+                    counter.completion.signal(true);
+                    counter.completion_frame = Some(counter.frame);
+                    if let Some(report) = &self.report_completion {
+                        report(world);
+                    }
+                    if let Some(system) = &self.next_state_system {
+                        let mut system = system.lock().unwrap();
+                        system.run((), world);
+                        system.apply_buffers(world);
+                    }
+                }
+                let next = if self.next_state_system.is_some() {
+                    world
+                        .remove_resource::<NextLoadingTarget<S>>()
+                        .map(|target| target.0)
+                } else {
+                    self.next_state.clone()
+                };
+                // the lazily computed target may only arrive on a later frame,
+                // and that is when the transition happens
+                let transitioning = match self.next_state_system {
+                    Some(_) => next.is_some(),
+                    None => just_completed,
+                };
+                if transitioning {
+                    let mut completion = world.resource_mut::<ProgressCompletion<S>>();
+                    completion.transition_frame = Some(completion.frame + transition_delay);
+                }
+                return next;
+            }
+        } else if self.max_duration.is_some_and(|max| elapsed >= max) {
+            return self.timeout_state.clone();
+        }
+        None
+    }
+
+    /// Send the event for `secondary_completion`, if it is time
+    fn check_secondary(&self, world: &mut World) {
+        let subset = match self.secondary_completion {
+            Some(subset) => subset,
+            None => return,
+        };
+        let mut counter = world.resource_mut::<ProgressCounter>();
+        if counter.secondary_completed {
+            return;
+        }
+        let all = counter.progress_complete();
+        let hidden = counter.progress_hidden();
+        let part = match subset {
+            ProgressSubset::Visible => Progress {
+                done: all.done - hidden.done,
+                total: all.total - hidden.total,
+            },
+            ProgressSubset::Hidden => hidden,
+        };
+        let epsilon = self.completion_epsilon;
+        if part.is_ready_within(epsilon) || all.is_ready_within(epsilon) {
+            counter.secondary_completed = true;
+            world
+                .resource_mut::<Events<SecondaryProgressComplete<S>>>()
+                .send(SecondaryProgressComplete::default());
+        }
     }
-    HiddenProgress(Progress {
-        done: *count - 1,
-        total: N,
-    })
 }
 
-/// Dummy system to wait for a time duration
+/// Which part of the progress to use for [`ProgressPlugin::secondary_completion`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressSubset {
+    /// Only the visible progress (as returned by `ProgressCounter::progress`)
+    Visible,
+    /// Only the hidden progress
+    Hidden,
+}
+
+/// Event sent when the part of the work selected with [`ProgressPlugin::secondary_completion`] completes
+pub struct SecondaryProgressComplete<S: StateData> {
+    _pd: PhantomData<S>,
+}
+
+impl<S: StateData> Default for SecondaryProgressComplete<S> {
+    fn default() -> Self {
+        SecondaryProgressComplete { _pd: PhantomData }
+    }
+}
+
+/// Resource to set the state to go to, when using [`ProgressPlugin::continue_to_lazy`]
 ///
-/// May be useful for testing/debug/workaround purposes.
-pub fn dummy_system_wait_millis<const MILLIS: u64>(
-    mut state: Local<Option<std::time::Instant>>,
-) -> HiddenProgress {
-    let end = state
-        .unwrap_or_else(|| std::time::Instant::now() + std::time::Duration::from_millis(MILLIS));
-    *state = Some(end);
-    HiddenProgress((std::time::Instant::now() > end).into())
+/// It is removed again when the transition is performed.
+#[derive(Resource, Debug, Clone)]
+pub struct NextLoadingTarget<S: StateData>(pub S);
+
+/// Label to control system execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub enum ProgressSystemLabel {
+    /// All systems tracking progress should run after this label
+    ///
+    /// The label is only needed for `at_start` exclusive systems.
+    /// Any parallel system or exclusive system at a different position
+    /// will run after it automatically.
+    Preparation,
+    /// Any system reading progress should ran after this label.
+    /// All systems wrapped in [`track`] automatically get this label.
+    Tracking,
+    /// All systems tracking progress should run before this label
+    ///
+    /// The label is only needed for `at_end` exclusive systems.
+    /// Any parallel system or exclusive system at a different position
+    /// will run before it automatically.
+    CheckProgress,
+}
+
+/// Label for the tracked systems added by the user, as opposed to the built-in ones
+///
+/// The built-in tracking systems (for assets and scenes) run before these, so
+/// that the user's tracked systems see their progress of the current frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+struct UserTracking;
+
+/// The phases of [`ProgressSystemLabel`], as distinct types
+///
+/// Every system that gets a [`ProgressSystemLabel`] also gets the equivalent
+/// label from here, so they can be used interchangeably. These are useful if
+/// you want to refer to one specific phase at the type level, like in generic
+/// bounds.
+pub mod label {
+    use bevy_ecs::prelude::*;
+
+    /// Type-level version of [`ProgressSystemLabel::Preparation`](crate::ProgressSystemLabel::Preparation)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+    pub struct Preparation;
+
+    /// Type-level version of [`ProgressSystemLabel::Tracking`](crate::ProgressSystemLabel::Tracking)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+    pub struct Tracking;
+
+    /// Type-level version of [`ProgressSystemLabel::CheckProgress`](crate::ProgressSystemLabel::CheckProgress)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+    pub struct CheckProgress;
+}
+
+/// Resource for tracking overall progress
+///
+/// This resource is automatically created when entering a state that was
+/// configured using [`ProgressPlugin`], and removed when exiting it.
+#[derive(Default, Resource)]
+pub struct ProgressCounter {
+    // use atomics to track overall progress,
+    // so that we can avoid mut access in tracked systems,
+    // allowing them to run in parallel
+    done: AtomicU32,
+    total: AtomicU32,
+    done_hidden: AtomicU32,
+    total_hidden: AtomicU32,
+    persisted: Progress,
+    persisted_hidden: Progress,
+    entered_at: Option<Instant>,
+    frame: u64,
+    best_visible: Progress,
+    completed: bool,
+    completion_frame: Option<u64>,
+    seen_work: bool,
+    // total of the work carried into this entry, when the state was entered
+    seeded_total: u32,
+    // the part of `persisted` that came from the `ReportedProgress` tally
+    reported: Progress,
+    completion: CompletionSignal,
+    // how many tracked systems reported progress on the current frame
+    systems_tracked: AtomicU32,
+    // the visible progress to report while `FreezeDisplayedProgress` exists
+    frozen: Option<Progress>,
+    expected_total: u32,
+    secondary_completed: bool,
+    observers: Mutex<Vec<Observer>>,
+    observer_throttle: ObserverThrottle,
+    // the value observers were last called with, and when
+    last_observed: Option<(Progress, Instant)>,
+    // the diagnostics enabled for the state that was entered
+    diagnostics: Option<ProgressDiagnostics>,
+    // diagnostics, only populated if enabled
+    systems: Mutex<HashMap<&'static str, Progress>>,
+    history: Vec<ProgressSample>,
+    // identifies the entry into a loading state that this counter was created for
+    generation: u64,
+}
+
+/// Source of the `generation` of every new [`ProgressCounter`]
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+impl ProgressCounter {
+    /// Get the latest overall progress information
+    ///
+    /// This is the combined total of all systems.
+    ///
+    /// To get correct information, make sure that you call this function only after
+    /// all your systems that track progress finished.
+    ///
+    /// This does not include "hidden" progress. To get the full "real" total, use
+    /// `progress_complete`.
+    ///
+    /// Use this method for progress bars and other things that indicate/report
+    /// progress information to the user.
+    ///
+    /// While the [`FreezeDisplayedProgress`] resource exists, this keeps returning
+    /// the value from the frame when it was inserted.
+    pub fn progress(&self) -> Progress {
+        self.frozen.unwrap_or_else(|| self.progress_actual())
+    }
+
+    /// Get the latest visible progress, ignoring `FreezeDisplayedProgress`
+    fn progress_actual(&self) -> Progress {
+        let raw = self.progress_visible();
+        Progress {
+            total: raw.total.max(self.expected_total),
+            ..raw
+        }
+    }
+
+    /// Get the latest visible progress, as reported by the tracked systems
+    ///
+    /// Without any of the display adjustments of [`progress`](Self::progress).
+    fn progress_visible(&self) -> Progress {
+        let total = self.total.load(MemOrdering::Acquire);
+        let done = self.done.load(MemOrdering::Acquire);
+
+        Progress { done, total }
+    }
+
+    /// Get the total (visible) units of work, as reported by the tracked systems
+    ///
+    /// Unlike the total returned by [`progress`](Self::progress), this does not
+    /// account for the expected total (see [`set_expected_total`](Self::set_expected_total)).
+    pub fn reported_total(&self) -> u32 {
+        self.total.load(MemOrdering::Acquire)
+    }
+
+    /// Get the expected total (visible) units of work
+    ///
+    /// See [`set_expected_total`](Self::set_expected_total).
+    pub fn expected_total(&self) -> u32 {
+        self.expected_total
+    }
+
+    /// Set how many (visible) units of work you expect there to be in total
+    ///
+    /// Useful if you know the total up front, but your systems only report
+    /// their work gradually. Otherwise, a progress bar could briefly show a
+    /// total that is too small (and a fraction that is too large), until all
+    /// systems have reported.
+    ///
+    /// The total returned by [`progress`](Self::progress) is the greater of the
+    /// expected total, and the total reported by the tracked systems (see
+    /// [`reported_total`](Self::reported_total)). This only affects the displayed
+    /// progress: the state transition only depends on the work actually reported
+    /// (`progress_complete`). If your expectation turns out to be too high, lower
+    /// it, or the visible progress will not reach 100%.
+    ///
+    /// The expected total stays in effect for the rest of the current state.
+    /// Also see [`ProgressPlugin::with_expected_total`].
+    pub fn set_expected_total(&mut self, total: u32) {
+        self.expected_total = total;
+    }
+
+    /// Get the latest overall (visible) progress, without ever going backwards
+    ///
+    /// Like [`progress`](Self::progress), but the completed fraction is guaranteed
+    /// to never decrease from one frame to the next (within the same entry into
+    /// the loading state). Use it for progress bars, if your systems might
+    /// revise their totals during loading (say, because they discovered more or
+    /// less work than initially expected), and you want to avoid the bar jumping
+    /// backwards.
+    ///
+    /// If the actual fraction is lower than the best one seen on previous frames,
+    /// `done` is adjusted (rounding up) to represent the best fraction, relative
+    /// to the current `total`. If the total shrinks, `done` is clamped, so it
+    /// never exceeds `total`.
+    pub fn progress_monotonic(&self) -> Progress {
+        let mut current = self.progress();
+        current.done = current.done.min(current.total);
+        let best = self.best_visible;
+        if current.total == 0 || best.total == 0 {
+            return current;
+        }
+        let current_frac = current.done as u64 * best.total as u64;
+        let best_frac = best.done as u64 * current.total as u64;
+        if current_frac >= best_frac {
+            return current;
+        }
+        let done =
+            (best.done as u64 * current.total as u64 + best.total as u64 - 1) / best.total as u64;
+        Progress {
+            done: (done as u32).min(current.total),
+            total: current.total,
+        }
+    }
+
+    /// Get the latest visible and hidden progress, as a `(visible, hidden)` pair
+    ///
+    /// Useful if you want to display both, say, as a main bar and a "details"
+    /// bar. Their sum is always the value returned by `progress_complete`.
+    ///
+    /// These are the values actually reported by the tracked systems. Unlike
+    /// [`progress`](Self::progress), the visible part is not affected by
+    /// [`FreezeDisplayedProgress`] or by the expected total (see
+    /// [`set_expected_total`](Self::set_expected_total)).
+    ///
+    /// The values are read together, so they are consistent with each other,
+    /// as long as no tracked systems are running at the same time. To be sure
+    /// of that, call this from a system ordered after
+    /// [`ProgressSystemLabel::Tracking`], just like for the other methods.
+    pub fn split(&self) -> (Progress, Progress) {
+        (self.progress_visible(), self.progress_hidden())
+    }
+
+    /// Get the latest hidden progress only
+    fn progress_hidden(&self) -> Progress {
+        let total = self.total_hidden.load(MemOrdering::Acquire);
+        let done = self.done_hidden.load(MemOrdering::Acquire);
+
+        Progress { done, total }
+    }
+
+    /// Get the latest overall (visible) progress, combined with some additional sources
+    ///
+    /// The additional sources are only accounted into the returned value, not
+    /// into the counter itself, so they do not affect the state transition.
+    /// Useful for things like debug overlays, that want to include extra
+    /// information that is not part of the loading logic.
+    pub fn progress_including(&self, sources: &[&dyn ProgressSource]) -> Progress {
+        sources
+            .iter()
+            .fold(self.progress(), |sum, source| sum + source.progress())
+    }
+
+    /// Get the latest overall progress information
+    ///
+    /// This is the combined total of all systems.
+    ///
+    /// To get correct information, make sure that you call this function only after
+    /// all your systems that track progress finished
+    ///
+    /// This includes "hidden" progress. To get only the "visible" progress, use
+    /// `progress`.
+    ///
+    /// This is the method to be used for things like state transitions, and other
+    /// use cases that must account for the "true" actual progress of the
+    /// registered systems.
+    pub fn progress_complete(&self) -> Progress {
+        let total =
+            self.total.load(MemOrdering::Acquire) + self.total_hidden.load(MemOrdering::Acquire);
+        let done =
+            self.done.load(MemOrdering::Acquire) + self.done_hidden.load(MemOrdering::Acquire);
+
+        Progress { done, total }
+    }
+
+    /// Add some amount of progress to the running total for the current frame.
+    ///
+    /// In most cases you do not want to call this function yourself.
+    /// Let your systems return a [`Progress`] and wrap them in [`track`] instead.
+    pub fn manually_track(&self, progress: Progress) {
+        self.total.fetch_add(progress.total, MemOrdering::Release);
+        // use `min` to clamp in case a bad user provides `done > total`
+        self.done
+            .fetch_add(progress.done.min(progress.total), MemOrdering::Release);
+    }
+
+    /// Add some amount of "hidden" progress to the running total for the current frame.
+    ///
+    /// Hidden progress counts towards the true total (like for triggering the
+    /// state transition) as reported by the `progress_complete` method, but is not
+    /// counted by the `progress` method. The intention is that it should not
+    /// affect things like progress bars and other user-facing indicators.
+    ///
+    /// In most cases you do not want to call this function yourself.
+    /// Let your systems return a [`Progress`] and wrap them in [`track`] instead.
+    pub fn manually_track_hidden(&self, progress: HiddenProgress) {
+        self.total_hidden
+            .fetch_add(progress.0.total, MemOrdering::Release);
+        // use `min` to clamp in case a bad user provides `done > total`
+        self.done_hidden
+            .fetch_add(progress.0.done.min(progress.0.total), MemOrdering::Release);
+    }
+
+    /// Overwrite the running total of (visible) units of work for the current frame.
+    ///
+    /// Unlike [`manually_track`](Self::manually_track), this does not add to the
+    /// value reported by other systems, but replaces it. It is intended for fully
+    /// manual tracking, if you compute the exact overall numbers yourself.
+    ///
+    /// Note that the counter is reset at the start of every frame (to any persisted
+    /// progress), so you need to call this every frame, after
+    /// [`ProgressSystemLabel::Preparation`]. Any tracked systems running after you
+    /// will still add on top of the value you set.
+    pub fn set_total(&self, total: u32) {
+        self.total.store(total, MemOrdering::Release);
+    }
+
+    /// Overwrite the running count of completed (visible) units of work for the current frame.
+    ///
+    /// See [`set_total`](Self::set_total) for the caveats.
+    pub fn set_done(&self, done: u32) {
+        self.done.store(done, MemOrdering::Release);
+    }
+
+    /// Get the latest progress reported by each individual tracked system
+    ///
+    /// The values include both visible and hidden progress. Systems are
+    /// identified by their type name.
+    ///
+    /// This is only recorded if enabled in [`ProgressDiagnostics`] (see
+    /// [`ProgressPlugin::verbose`]) for the current loading state. Otherwise,
+    /// it is empty.
+    pub fn systems_progress(&self) -> Vec<(&'static str, Progress)> {
+        let systems = self.systems.lock().unwrap();
+        systems.iter().map(|(name, p)| (*name, *p)).collect()
+    }
+
+    /// Get the recorded history of progress values, one sample per frame
+    ///
+    /// This is only recorded if enabled in [`ProgressDiagnostics`] (see
+    /// [`ProgressPlugin::verbose`]) for the current loading state. Otherwise,
+    /// it is empty.
+    pub fn history(&self) -> &[ProgressSample] {
+        &self.history
+    }
+
+    /// Get a future that resolves when progress completes
+    ///
+    /// For integrating with async code: `.await` it to wait for loading to
+    /// finish. See [`CompletionHandle`] for details.
+    pub fn wait_handle(&self) -> CompletionHandle {
+        self.completion.handle()
+    }
+
+    /// Add a callback to be called whenever the visible progress changes
+    ///
+    /// Observers are called by the check-progress system, at the end of every
+    /// frame where the final value of [`progress`](Self::progress) differs from
+    /// the one they were last called with. This can be limited further, with
+    /// [`set_observer_throttle`](Self::set_observer_throttle).
+    ///
+    /// Observers are stored in the counter, so they are removed along with it,
+    /// when exiting the loading state.
+    pub fn add_observer(&self, observer: impl FnMut(Progress) + Send + 'static) {
+        self.observers.lock().unwrap().push(Box::new(observer));
+    }
+
+    /// Limit how often observers are called
+    ///
+    /// Useful for expensive observers (like sending telemetry over the network),
+    /// that should not be called for every tiny increment. See [`ObserverThrottle`]
+    /// for the exact rules.
+    pub fn set_observer_throttle(&mut self, throttle: ObserverThrottle) {
+        self.observer_throttle = throttle;
+    }
+
+    /// Call the observers, if the throttle allows
+    ///
+    /// `epsilon` is the completion epsilon of the loading state, so that
+    /// observers are always told about completion, despite the throttle.
+    fn notify_observers(&mut self, epsilon: u32) {
+        let progress = self.progress();
+        let now = Instant::now();
+        let notify = match self.last_observed {
+            None => true,
+            Some((last, _)) if last == progress => false,
+            Some(_) if progress.is_ready_within(epsilon) => true,
+            Some((last, at)) => self.observer_throttle.allows(last, progress, now - at),
+        };
+        if notify {
+            self.last_observed = Some((progress, now));
+            for observer in self.observers.get_mut().unwrap().iter_mut() {
+                observer(progress);
+            }
+        }
+    }
+
+    /// Take the recorded history, leaving it empty
+    ///
+    /// Useful for periodically exporting the samples somewhere, without
+    /// the history growing indefinitely.
+    pub fn drain_history(&mut self) -> Vec<ProgressSample> {
+        std::mem::take(&mut self.history)
+    }
+
+    /// Get the number of frames that have passed in the loading state
+    ///
+    /// The first frame in the state is frame `1`.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Get the frame on which all progress first completed
+    ///
+    /// Returns `None` if it has not completed yet. Otherwise, this is the value
+    /// that [`frame`](Self::frame) had on that frame. If a minimum duration was
+    /// configured, this is the frame when progress completed after it elapsed.
+    pub fn completion_frame(&self) -> Option<u64> {
+        self.completion_frame
+    }
+
+    /// Get how much time has passed since entering the loading state
+    pub fn elapsed(&self) -> Duration {
+        self.entered_at.map(|t| t.elapsed()).unwrap_or_default()
+    }
+
+    /// Persist progress for the rest of the current state
+    pub fn persist_progress(&mut self, progress: Progress) {
+        self.manually_track(progress);
+        self.persisted += progress;
+    }
+
+    /// Persist hidden progress for the rest of the current state
+    pub fn persist_progress_hidden(&mut self, progress: HiddenProgress) {
+        self.manually_track_hidden(progress);
+        self.persisted_hidden += progress.0;
+    }
+}
+
+/// Resource with the progress accumulated over previous entries into loading states
+///
+/// Only used for states configured with [`ProgressPlugin::accumulate_across_entries`].
+#[derive(Resource)]
+pub struct AccumulatedProgress<S: StateData> {
+    map: HashMap<S, (Progress, Progress)>,
+}
+
+impl<S: StateData> Default for AccumulatedProgress<S> {
+    fn default() -> Self {
+        AccumulatedProgress {
+            map: Default::default(),
+        }
+    }
+}
+
+impl<S: StateData> AccumulatedProgress<S> {
+    /// Get the (visible, hidden) progress accumulated for the given state
+    pub fn get(&self, state: &S) -> Option<(Progress, HiddenProgress)> {
+        self.map
+            .get(state)
+            .map(|(visible, hidden)| (*visible, HiddenProgress(*hidden)))
+    }
+
+    /// Forget all accumulated progress for the given state
+    ///
+    /// The next time the state is entered, progress will start from zero.
+    /// If the state is currently active, this only takes effect after
+    /// exiting it and entering it again.
+    pub fn reset(&mut self, state: &S) {
+        self.map.remove(state);
+    }
+}
+
+/// Resource with the tally of completed sub-phases, for each parent state of type `S`
+///
+/// Created when the first sub-phase configured with
+/// [`ProgressPlugin::report_completion_to`] completes. When a parent state is
+/// entered, its [`ProgressCounter`] is seeded with its tally from here.
+#[derive(Resource)]
+pub struct ReportedProgress<S: StateData> {
+    map: HashMap<S, Progress>,
+}
+
+impl<S: StateData> Default for ReportedProgress<S> {
+    fn default() -> Self {
+        ReportedProgress {
+            map: Default::default(),
+        }
+    }
+}
+
+impl<S: StateData> ReportedProgress<S> {
+    /// Get the progress reported to the given (parent) state so far
+    pub fn get(&self, state: &S) -> Option<Progress> {
+        self.map.get(state).copied()
+    }
+
+    /// Forget all progress reported to the given (parent) state
+    pub fn reset(&mut self, state: &S) {
+        self.map.remove(state);
+    }
+}
+
+/// Marker resource to freeze the visible progress, while it exists
+///
+/// Insert it to make [`ProgressCounter::progress`] (and everything based on
+/// it, like `progress_monotonic`) keep returning the value from the last frame
+/// before the resource was inserted, so that progress bars stop moving. For
+/// example, while a confirmation dialog is shown over the loading screen.
+/// Remove it to unfreeze.
+///
+/// This only affects what is displayed: tracked systems keep running, and the
+/// actual progress (`progress_complete`) keeps updating. If all work completes
+/// while frozen, the state transition still happens as usual. If you also want
+/// to hold the transition, use [`ProgressPlugin::on_complete_check`].
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct FreezeDisplayedProgress;
+
+/// Resource listing all the tracked states that are currently active
+///
+/// Every [`ProgressPlugin`] adds its state here when it is entered, and
+/// removes it when it is exited. Useful for debug overlays, if your app has
+/// many loading states (possibly of different types, with `iyes_loopless`).
+///
+/// Each entry has a snapshot of the progress, updated at the end of every
+/// frame by the check-progress system. So, it always has the final values
+/// for the previous frame (or the current frame, if read after
+/// [`ProgressSystemLabel::CheckProgress`]). The snapshots are plain values,
+/// copied out of the counter, so they can be read at any time, from any
+/// system with `Res<ActiveProgressStates>`.
+///
+/// Note that there is only one [`ProgressCounter`]. If several tracked states
+/// are active at the same time, they share it, and so their snapshots are
+/// the same.
+#[derive(Resource, Debug, Default)]
+pub struct ActiveProgressStates {
+    states: Vec<ActiveProgressState>,
+}
+
+/// An entry in [`ActiveProgressStates`]
+#[derive(Debug)]
+pub struct ActiveProgressState {
+    /// The type name of the state type
+    pub type_name: &'static str,
+    /// The state value, formatted with `Debug`
+    ///
+    /// Only for display; entries are looked up by the state value itself.
+    pub name: String,
+    type_id: TypeId,
+    value: Box<dyn Any + Send + Sync>,
+    /// The visible progress (as returned by `ProgressCounter::progress`)
+    pub visible: Progress,
+    /// The hidden progress only
+    pub hidden: Progress,
+}
+
+impl ActiveProgressState {
+    /// Is this the entry for the given state?
+    pub fn is<S: StateData>(&self, state: &S) -> bool {
+        self.type_id == TypeId::of::<S>() && self.value.downcast_ref::<S>() == Some(state)
+    }
+}
+
+impl ActiveProgressStates {
+    /// Iterate over all the active tracked states
+    pub fn iter(&self) -> impl Iterator<Item = &ActiveProgressState> {
+        self.states.iter()
+    }
+
+    /// How many tracked states are active?
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Are there no active tracked states?
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Get the entry for the given state, if it is active
+    pub fn get<S: StateData>(&self, state: &S) -> Option<&ActiveProgressState> {
+        self.states.iter().find(|entry| entry.is(state))
+    }
+
+    fn insert<S: StateData>(&mut self, state: &S) {
+        if self.get(state).is_none() {
+            self.states.push(ActiveProgressState {
+                type_name: std::any::type_name::<S>(),
+                name: format!("{:?}", state),
+                type_id: TypeId::of::<S>(),
+                value: Box::new(state.clone()),
+                visible: Progress::default(),
+                hidden: Progress::default(),
+            });
+        }
+    }
+
+    fn remove<S: StateData>(&mut self, state: &S) {
+        self.states.retain(|entry| !entry.is(state));
+    }
+
+    /// Update the snapshots of all active states of type `S`
+    fn update<S: StateData>(&mut self, (visible, hidden): (Progress, Progress)) {
+        for entry in self.states.iter_mut() {
+            if entry.type_id == TypeId::of::<S>() {
+                entry.visible = visible;
+                entry.hidden = hidden;
+            }
+        }
+    }
+}
+
+/// Resource to tell whether the progress of the current frame is final
+///
+/// The [`ProgressCounter`] is reset at the start of every frame, and then
+/// tracked systems add their progress to it. So, if you read it too early,
+/// you get incomplete values. This flag is set by the check-progress system
+/// (see [`ProgressSystemLabel::CheckProgress`]), after which all progress for
+/// the frame has been reported, and cleared again when the counter is reset.
+///
+/// Use it in systems that must be sure to see the final values, like UI
+/// systems in a later stage, to verify that they are ordered correctly.
+/// It is only updated while in a loading state.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressFinalized {
+    finalized: bool,
+}
+
+impl ProgressFinalized {
+    /// Has all progress for the current frame been reported?
+    pub fn is_finalized(&self) -> bool {
+        self.finalized
+    }
+
+    /// Like [`is_finalized`](Self::is_finalized), but log a warning if not
+    ///
+    /// `reader` identifies what is reading the progress, for the warning.
+    pub fn warn_if_not_finalized(&self, reader: &str) -> bool {
+        if !self.finalized {
+            warn!(
+                "{} is reading progress before it is final for this frame; order it after `ProgressSystemLabel::CheckProgress`.",
+                reader
+            );
+        }
+        self.finalized
+    }
+}
+
+/// Resource with the human-readable status of what is currently being worked on
+///
+/// Systems added using `track_progress_with_status` report their status text
+/// here, every frame, as long as their progress is not complete. UI can use
+/// this to show what the loading screen is doing right now.
+///
+/// Just like [`ProgressCounter`], this resource is created when entering a
+/// state configured using [`ProgressPlugin`], and removed when exiting it. It
+/// is cleared at the start of every frame, so (just like progress) make sure
+/// to only read it after all tracked systems have run.
+#[derive(Default, Resource)]
+pub struct ProgressStatus {
+    // tracked systems run in parallel, so we need a lock
+    active: Mutex<Vec<Cow<'static, str>>>,
+}
+
+impl ProgressStatus {
+    /// Get the status text of all systems that are currently working
+    ///
+    /// They are in the order they were reported in. Because tracked systems
+    /// may run in parallel, that order is not always the same.
+    pub fn active(&self) -> Vec<Cow<'static, str>> {
+        self.active.lock().unwrap().clone()
+    }
+
+    /// Get the status text that was reported last, if any
+    pub fn latest(&self) -> Option<Cow<'static, str>> {
+        self.active.lock().unwrap().last().cloned()
+    }
+}
+
+/// Trait for anything that can be queried for its current progress
+///
+/// See [`ProgressCounter::progress_including`].
+pub trait ProgressSource {
+    /// Get the current progress
+    fn progress(&self) -> Progress;
+}
+
+impl ProgressSource for Progress {
+    fn progress(&self) -> Progress {
+        *self
+    }
+}
+
+impl ProgressSource for ProgressCounter {
+    fn progress(&self) -> Progress {
+        ProgressCounter::progress(self)
+    }
+}
+
+/// Trait for all types that can be returned by systems to report progress
+pub trait ApplyProgress {
+    /// Account the value into the total progress for this frame
+    fn apply_progress(self, total: &ProgressCounter);
+
+    /// Get the (visible, hidden) progress that this value accounts for
+    ///
+    /// Used for diagnostics and status reporting. The default implementation
+    /// accounts the value into a temporary [`ProgressCounter`], which works,
+    /// but is not cheap; override it if you can.
+    fn split(self) -> (Progress, Progress)
+    where
+        Self: Sized,
+    {
+        let counter = ProgressCounter::default();
+        self.apply_progress(&counter);
+        counter.split()
+    }
+}
+
+impl ApplyProgress for Progress {
+    fn apply_progress(self, total: &ProgressCounter) {
+        total.manually_track(self);
+    }
+
+    fn split(self) -> (Progress, Progress) {
+        (self, Progress::default())
+    }
+}
+
+impl ApplyProgress for HiddenProgress {
+    fn apply_progress(self, total: &ProgressCounter) {
+        total.manually_track_hidden(self);
+    }
+
+    fn split(self) -> (Progress, Progress) {
+        (Progress::default(), self.0)
+    }
+}
+
+impl<T: ApplyProgress> ApplyProgress for (T, T) {
+    fn apply_progress(self, total: &ProgressCounter) {
+        self.0.apply_progress(total);
+        self.1.apply_progress(total);
+    }
+
+    fn split(self) -> (Progress, Progress) {
+        let (visible0, hidden0) = self.0.split();
+        let (visible1, hidden1) = self.1.split();
+        (visible0 + visible1, hidden0 + hidden1)
+    }
+}
+
+/// Resource for detecting the frame when progress in state `S` completed
+///
+/// This resource is automatically created by [`ProgressPlugin`], and updated
+/// by its check-progress system. It is not removed when exiting the state, so
+/// that it can also be used from systems that do not run in the loading state.
+///
+/// See `progress_just_completed` (in the backend modules, and the prelude) for
+/// a run condition based on it.
+#[derive(Resource)]
+pub struct ProgressCompletion<S: StateData> {
+    frame: u64,
+    transition_frame: Option<u64>,
+    _pd: PhantomData<S>,
+}
+
+impl<S: StateData> Default for ProgressCompletion<S> {
+    fn default() -> Self {
+        ProgressCompletion {
+            frame: 0,
+            transition_frame: None,
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<S: StateData> ProgressCompletion<S> {
+    /// Is the current frame the one where we transition out of the loading state?
+    ///
+    /// This is true for exactly one frame each time progress completes (even if no
+    /// `continue_to` state was configured): the frame on which the state
+    /// transition is performed.
+    /// With `continue_to_lazy`, that is when the [`NextLoadingTarget`] is
+    /// found, which may be some frames after progress completed.
+    ///
+    /// Without `iyes_loopless`, the transition happens right after the
+    /// check-progress system, so only systems in later stages (like
+    /// `CoreStage::PostUpdate`) will see it.
+    ///
+    /// With `iyes_loopless`, the check-progress system runs at the very end of
+    /// the frame, and the transition happens on the following frame, so this is
+    /// true during that entire following frame.
+    pub fn is_transitioning(&self) -> bool {
+        self.transition_frame == Some(self.frame)
+    }
+}
+
+/// Shared logic of the `progress_just_completed` run condition in both backends
+fn just_completed<S: StateData>(completion: Option<&ProgressCompletion<S>>) -> bool {
+    completion.is_some_and(|c| c.is_transitioning())
+}
+
+/// Shared logic of the `progress_at_least` run condition in both backends
+fn progress_reached(counter: Option<&ProgressCounter>, fraction: f32) -> bool {
+    counter.is_some_and(|counter| {
+        let progress = counter.progress();
+        progress.total != 0 && progress.fraction() >= fraction
+    })
+}
+
+fn completion_next_frame<S: StateData>(mut completion: ResMut<ProgressCompletion<S>>) {
+    completion.frame += 1;
+}
+
+/// The end of the pipe for all systems wrapped with `track_progress`
+///
+/// `name` identifies the system, for diagnostics. `status` is the status text
+/// set using `track_progress_with_status`.
+///
+/// If the counter does not exist (say, because the system was added to a
+/// schedule where it runs outside of the loading state), the progress is
+/// ignored and we warn (once per system) instead of panicking.
+fn apply_tracked_progress<T: ApplyProgress>(
+    name: &'static str,
+    status: Option<Cow<'static, str>>,
+) -> impl FnMut(In<T>, Option<Res<ProgressCounter>>, Option<Res<ProgressStatus>>, Local<bool>) {
+    move |In(progress), counter, progress_status, mut warned| {
+        let counter = match counter {
+            Some(counter) => counter,
+            None => {
+                if !*warned {
+                    warn!(
+                        "Progress-tracked system {} ran while there is no `ProgressCounter`; its progress will be ignored. \
+                         Make sure it only runs in a state configured with `ProgressPlugin`.",
+                        name
+                    );
+                    *warned = true;
+                }
+                return;
+            }
+        };
+        counter.systems_tracked.fetch_add(1, MemOrdering::Release);
+        let per_system = counter.diagnostics.as_ref().is_some_and(|d| d.per_system);
+        if per_system || status.is_some() {
+            let (visible, hidden) = progress.split();
+            counter.manually_track(visible);
+            counter.manually_track_hidden(HiddenProgress(hidden));
+            // clamped, just like when tracking
+            let complete = Progress {
+                done: visible.done.min(visible.total) + hidden.done.min(hidden.total),
+                total: visible.total + hidden.total,
+            };
+            if per_system {
+                counter.systems.lock().unwrap().insert(name, complete);
+            }
+            if let (Some(status), Some(progress_status)) = (&status, progress_status) {
+                if !complete.is_ready() {
+                    progress_status.active.lock().unwrap().push(status.clone());
+                }
+            }
+        } else {
+            progress.apply_progress(&*counter);
+        }
+    }
+}
+
+fn entity_progress(q: Query<&ProgressComponent>) -> Progress {
+    q.iter().fold(Progress::default(), |sum, c| sum + c.0)
+}
+
+fn loadstate_enter<S: StateData>(
+    state: S,
+    expected_total: u32,
+    observer_throttle: ObserverThrottle,
+    diagnostics: Option<ProgressDiagnostics>,
+) -> impl FnMut(
+    Commands,
+    Option<Res<AccumulatedProgress<S>>>,
+    Option<Res<ReportedProgress<S>>>,
+    ResMut<ActiveProgressStates>,
+) {
+    move |mut commands, accumulated, reported, mut active| {
+        // replaces any counter left over from a soft transition
+        commands.remove_resource::<BackgroundProgress<S>>();
+        active.insert(&state);
+        let (persisted, persisted_hidden) = accumulated
+            .and_then(|accumulated| accumulated.get(&state))
+            .unwrap_or_default();
+        // completed sub-phases, see `report_completion_to`
+        let reported = reported
+            .and_then(|reported| reported.get(&state))
+            .unwrap_or_default();
+        let persisted = persisted + reported;
+        commands.insert_resource(ProgressCounter {
+            persisted,
+            persisted_hidden: persisted_hidden.0,
+            seeded_total: persisted.total + persisted_hidden.0.total,
+            reported,
+            entered_at: Some(Instant::now()),
+            expected_total,
+            observer_throttle,
+            diagnostics: diagnostics.clone(),
+            generation: NEXT_GENERATION.fetch_add(1, MemOrdering::Relaxed),
+            ..Default::default()
+        });
+        commands.insert_resource(ProgressStatus::default());
+    }
+}
+
+fn loadstate_exit<S: StateData>(
+    state: S,
+    accumulate: bool,
+    soft_transition: bool,
+) -> impl FnMut(
+    Commands,
+    Option<Res<ProgressCounter>>,
+    Option<ResMut<AccumulatedProgress<S>>>,
+    ResMut<ActiveProgressStates>,
+) {
+    move |mut commands, counter, accumulated, mut active| {
+        active.remove(&state);
+        if let (true, Some(counter)) = (soft_transition, &counter) {
+            commands.insert_resource(BackgroundProgress {
+                from: state.clone(),
+                frame: counter.frame,
+                generation: counter.generation,
+            });
+        }
+        if let (true, Some(counter), Some(mut accumulated)) = (accumulate, counter, accumulated) {
+            // only completed work carries over, as nothing will report on
+            // any remaining work from this time around anymore, and without
+            // the reported tally, which is added again on the next entry
+            let visible = counter
+                .progress_visible()
+                .done
+                .saturating_sub(counter.reported.done);
+            let hidden = counter.progress_hidden().done;
+            accumulated.map.insert(
+                state.clone(),
+                (
+                    Progress {
+                        done: visible,
+                        total: visible,
+                    },
+                    Progress {
+                        done: hidden,
+                        total: hidden,
+                    },
+                ),
+            );
+        }
+        if !soft_transition {
+            commands.remove_resource::<ProgressCounter>();
+            commands.remove_resource::<ProgressStatus>();
+        }
+    }
+}
+
+/// Resource present while progress left over from state `S` is tracked in the background
+///
+/// See [`ProgressPlugin::soft_transition`].
+#[derive(Resource)]
+pub struct BackgroundProgress<S: StateData> {
+    /// The loading state that was exited
+    pub from: S,
+    /// The counter's frame at the time of the transition
+    frame: u64,
+    /// The counter's generation, to tell if it has been replaced since
+    generation: u64,
+}
+
+impl<S: StateData> BackgroundProgress<S> {
+    /// Is the counter still the one left over from `state`?
+    fn is_for(&self, state: &S, counter: &ProgressCounter) -> bool {
+        self.from == *state && self.generation == counter.generation
+    }
+}
+
+/// Like `next_frame`, but for a counter that survived a soft transition out of `state`
+fn background_next_frame<S: StateData>(state: S) -> impl FnMut(&mut World) {
+    move |world| {
+        let active = match (
+            world.get_resource::<BackgroundProgress<S>>(),
+            world.get_resource::<ProgressCounter>(),
+        ) {
+            (Some(background), Some(counter)) => background.is_for(&state, counter),
+            _ => false,
+        };
+        if active {
+            next_frame(world);
+        }
+    }
+}
+
+/// Remove the background counter, once all background work is done
+///
+/// If the counter has been replaced (by entering a loading state of another
+/// type), only the stale [`BackgroundProgress`] is removed.
+fn background_check<S: StateData>(
+    state: S,
+    epsilon: u32,
+) -> impl FnMut(Commands, Option<Res<ProgressCounter>>, Option<Res<BackgroundProgress<S>>>) {
+    move |mut commands, counter, background| {
+        let background = match background {
+            Some(background) if background.from == state => background,
+            _ => return,
+        };
+        match counter {
+            Some(counter) if background.is_for(&state, &counter) => {
+                // skip the frame of the transition itself, before the counter has been reset
+                if counter.frame > background.frame
+                    && counter.progress_complete().is_ready_within(epsilon)
+                {
+                    commands.remove_resource::<ProgressCounter>();
+                    commands.remove_resource::<ProgressStatus>();
+                    commands.remove_resource::<BackgroundProgress<S>>();
+                }
+            }
+            _ => commands.remove_resource::<BackgroundProgress<S>>(),
+        }
+    }
+}
+
+fn next_frame(world: &mut World) {
+    if let Some(mut finalized) = world.get_resource_mut::<ProgressFinalized>() {
+        finalized.finalized = false;
+    }
+
+    if let Some(status) = world.get_resource::<ProgressStatus>() {
+        status.active.lock().unwrap().clear();
+    }
+
+    let freeze = world.contains_resource::<FreezeDisplayedProgress>();
+
+    let mut counter = world.resource_mut::<ProgressCounter>();
+    counter.frame += 1;
+    counter.systems_tracked.store(0, MemOrdering::Release);
+
+    // keep the final value from the frame before the flag was set
+    if !freeze {
+        counter.frozen = None;
+    } else if counter.frozen.is_none() {
+        counter.frozen = Some(counter.progress_actual());
+    }
+
+    // remember the final value from the previous frame, for `progress_monotonic`
+    let best = counter.progress_monotonic();
+    if best.total != 0 {
+        counter.best_visible = best;
+    }
+
+    counter
+        .done
+        .store(counter.persisted.done, MemOrdering::Release);
+    counter
+        .total
+        .store(counter.persisted.total, MemOrdering::Release);
+
+    counter
+        .done_hidden
+        .store(counter.persisted_hidden.done, MemOrdering::Release);
+    counter
+        .total_hidden
+        .store(counter.persisted_hidden.total, MemOrdering::Release);
+}
+
+/// Dummy system to count for a number of frames
+///
+/// May be useful for testing/debug/workaround purposes.
+pub fn dummy_system_wait_frames<const N: u32>(mut count: Local<u32>) -> HiddenProgress {
+    if *count <= N {
+        *count += 1;
+    }
+    HiddenProgress(Progress {
+        done: *count - 1,
+        total: N,
+    })
+}
+
+/// Dummy system to wait for a time duration
+///
+/// May be useful for testing/debug/workaround purposes.
+pub fn dummy_system_wait_millis<const MILLIS: u64>(
+    mut state: Local<Option<std::time::Instant>>,
+) -> HiddenProgress {
+    let end = state
+        .unwrap_or_else(|| std::time::Instant::now() + std::time::Duration::from_millis(MILLIS));
+    *state = Some(end);
+    HiddenProgress((std::time::Instant::now() > end).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+
+    use crate::legacy::ProgressSystem as _;
+    use crate::{Progress, ProgressBackend, ProgressCounter, ProgressPlugin};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum AppState {
+        Loading,
+        Other,
+        Done,
+        Failed,
+    }
+
+    /// When the tracked work becomes ready
+    #[derive(Resource)]
+    struct ReadyAt(Instant);
+
+    fn work(ready_at: Res<ReadyAt>) -> Progress {
+        (Instant::now() >= ready_at.0).into()
+    }
+
+    fn current_state(app: &App) -> AppState {
+        *app.world.resource::<State<AppState>>().current()
+    }
+
+    /// The progress reported by [`report`]
+    #[derive(Resource, Default)]
+    struct Work(Progress);
+
+    fn report(work: Res<Work>) -> Progress {
+        work.0
+    }
+
+    /// An app using `plugin` (with the legacy backend), that tracks [`report`] while loading
+    fn loading_app(plugin: ProgressPlugin<AppState>) -> App {
+        let mut app = App::new();
+        app.add_state(AppState::Loading);
+        app.init_resource::<Work>();
+        app.add_plugin(plugin.with_backend(ProgressBackend::Legacy));
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading).with_system(report.track_progress()),
+        );
+        app
+    }
+
+    fn counter(app: &App) -> &ProgressCounter {
+        app.world.resource::<ProgressCounter>()
+    }
+
+    #[test]
+    fn percent_rounded_boundaries() {
+        let p = Progress {
+            done: 495,
+            total: 1000,
+        };
+        assert_eq!(p.percent(), 49);
+        assert_eq!(p.percent_rounded(), 50);
+        assert_eq!(p.fraction_rounded(), 0.5);
+
+        let p = Progress {
+            done: 5,
+            total: 1000,
+        };
+        assert_eq!(p.percent(), 0);
+        assert_eq!(p.percent_rounded(), 1);
+
+        let p = Progress {
+            done: 4,
+            total: 1000,
+        };
+        assert_eq!(p.percent_rounded(), 0);
+    }
+
+    #[cfg(feature = "iyes_loopless")]
+    #[test]
+    fn both_backends_in_same_app() {
+        use iyes_loopless::prelude::*;
+        use iyes_loopless::state::CurrentState;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        enum OtherState {
+            Idle,
+            Loading,
+            Done,
+        }
+
+        let mut app = App::new();
+        app.add_state(AppState::Loading);
+        app.add_loopless_state(OtherState::Idle);
+        app.insert_resource(ReadyAt(Instant::now()));
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .with_backend(ProgressBackend::Legacy),
+        );
+        app.add_plugin(
+            ProgressPlugin::new(OtherState::Loading)
+                .continue_to(OtherState::Done)
+                .with_backend(ProgressBackend::Loopless),
+        );
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading)
+                .with_system(crate::legacy::ProgressSystem::track_progress(work)),
+        );
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(OtherState::Loading)
+                .with_system(crate::loopless::ProgressSystem::track_progress(work))
+                .into(),
+        );
+
+        // one after the other, as the loading states would share the `ProgressCounter`
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::Done);
+        app.insert_resource(NextState(OtherState::Loading));
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(
+            app.world.resource::<CurrentState<OtherState>>().0,
+            OtherState::Done
+        );
+    }
+
+    #[cfg(feature = "assets")]
+    #[test]
+    fn asset_progress_is_seen_after_tracking_on_the_same_frame() {
+        use bevy_asset::{AssetPlugin, AssetServer};
+
+        use crate::asset::AssetsLoading;
+        use crate::ProgressSystemLabel;
+
+        /// The totals seen by a system ordered after the tracking label
+        #[derive(Resource, Default)]
+        struct Seen(Vec<u32>);
+
+        fn record(counter: Option<Res<ProgressCounter>>, mut seen: ResMut<Seen>) {
+            if let Some(counter) = counter {
+                seen.0.push(counter.progress().total);
+            }
+        }
+
+        let mut app = App::new();
+        app.add_plugin(AssetPlugin::default());
+        app.add_state(AppState::Loading);
+        app.init_resource::<Seen>();
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .track_assets()
+                .with_backend(ProgressBackend::Legacy),
+        );
+        // no tracked systems of our own, only the assets
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading)
+                .with_system(record.after(ProgressSystemLabel::Tracking)),
+        );
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .get_handle_untyped("never_loaded.png");
+        app.world
+            .resource_mut::<AssetsLoading>()
+            .add_weighted(&handle, 5);
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<Seen>().0, vec![5, 5, 5]);
+    }
+
+    /// Run the app until it leaves the loading state, returning the state and the time taken
+    fn run_timed(app: &mut App) -> (AppState, Duration) {
+        let start = Instant::now();
+        while current_state(app) == AppState::Loading {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "never transitioned"
+            );
+            app.update();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        (current_state(app), start.elapsed())
+    }
+
+    fn timed_app(ready_in: Duration, min: Duration, max: Duration) -> App {
+        let mut app = App::new();
+        app.add_state(AppState::Loading);
+        app.insert_resource(ReadyAt(Instant::now() + ready_in));
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .with_min_and_max_duration(min, max, AppState::Failed)
+                .with_backend(ProgressBackend::Legacy),
+        );
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading).with_system(work.track_progress()),
+        );
+        app
+    }
+
+    #[test]
+    fn completes_before_min_waits_for_min() {
+        let min = Duration::from_millis(100);
+        let mut app = timed_app(Duration::ZERO, min, Duration::from_secs(5));
+        let (state, elapsed) = run_timed(&mut app);
+        assert_eq!(state, AppState::Done);
+        assert!(elapsed >= min);
+    }
+
+    #[test]
+    fn completes_between_min_and_max_transitions_at_completion() {
+        let ready_in = Duration::from_millis(150);
+        let max = Duration::from_secs(5);
+        let mut app = timed_app(ready_in, Duration::from_millis(20), max);
+        let (state, elapsed) = run_timed(&mut app);
+        assert_eq!(state, AppState::Done);
+        assert!(elapsed >= ready_in);
+        assert!(elapsed < max);
+    }
+
+    #[test]
+    fn not_complete_by_max_fails() {
+        let max = Duration::from_millis(150);
+        let mut app = timed_app(Duration::from_secs(3600), Duration::from_millis(20), max);
+        let (state, elapsed) = run_timed(&mut app);
+        assert_eq!(state, AppState::Failed);
+        assert!(elapsed >= max);
+    }
+
+    #[test]
+    fn delta_since() {
+        let previous = Progress { done: 3, total: 10 };
+        assert_eq!(
+            Progress { done: 5, total: 12 }.delta_since(previous),
+            Progress { done: 2, total: 2 }
+        );
+        // the total shrank: saturates, rather than underflowing
+        assert_eq!(
+            Progress { done: 5, total: 8 }.delta_since(previous),
+            Progress { done: 2, total: 0 }
+        );
+    }
+
+    #[cfg(feature = "assets")]
+    #[test]
+    fn add_folder_tracks_every_handle() {
+        use bevy_asset::{AssetPath, HandleId, HandleUntyped};
+
+        use crate::asset::AssetsLoading;
+
+        let handles: Vec<_> = ["a.png", "b.png", "c.ogg"]
+            .into_iter()
+            .map(|path| HandleUntyped::weak(HandleId::from(AssetPath::from(path))))
+            .collect();
+        let mut loading = AssetsLoading::default();
+        loading.add_folder(&handles);
+        assert_eq!(loading.remaining(), 3);
+        assert!(!loading.is_ready());
+    }
+
+    #[test]
+    fn set_total_and_done_overwrite() {
+        let counter = ProgressCounter::default();
+        counter.manually_track(Progress { done: 1, total: 2 });
+        counter.set_total(10);
+        counter.set_done(4);
+        assert_eq!(counter.progress(), Progress { done: 4, total: 10 });
+        // tracking afterwards still adds on top
+        counter.manually_track(Progress { done: 1, total: 1 });
+        assert_eq!(counter.progress(), Progress { done: 5, total: 11 });
+    }
+
+    /// How many times the one-shot system ran
+    #[derive(Resource, Default)]
+    struct Fired(u32);
+
+    fn ready_on_third_frame(mut frames: Local<u32>) -> Progress {
+        *frames += 1;
+        (*frames >= 3).into()
+    }
+
+    fn fire(mut fired: ResMut<Fired>) {
+        fired.0 += 1;
+    }
+
+    #[test]
+    fn progress_just_completed_exactly_one_frame() {
+        let mut app = App::new();
+        app.add_state(AppState::Loading);
+        app.init_resource::<Fired>();
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .with_backend(ProgressBackend::Legacy),
+        );
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading)
+                .with_system(ready_on_third_frame.track_progress()),
+        );
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            fire.with_run_criteria(crate::legacy::progress_just_completed::<AppState>),
+        );
+
+        let mut transitioned_on = None;
+        for frame in 0..10 {
+            app.update();
+            if transitioned_on.is_none() && current_state(&app) == AppState::Done {
+                transitioned_on = Some(frame);
+            }
+            let expected = if transitioned_on.is_some() { 1 } else { 0 };
+            assert_eq!(app.world.resource::<Fired>().0, expected, "frame {}", frame);
+        }
+        assert!(transitioned_on.is_some());
+    }
+
+    #[test]
+    fn entities_become_ready_over_several_frames() {
+        use crate::ProgressComponent;
+
+        let mut app = App::new();
+        app.add_state(AppState::Loading);
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .track_entities()
+                .with_backend(ProgressBackend::Legacy),
+        );
+        let a = app.world.spawn(ProgressComponent(Progress::none(1))).id();
+        let b = app.world.spawn(ProgressComponent(Progress::none(2))).id();
+
+        app.update();
+        assert_eq!(
+            app.world.resource::<ProgressCounter>().progress(),
+            Progress { done: 0, total: 3 }
+        );
+
+        app.world
+            .entity_mut(a)
+            .insert(ProgressComponent(Progress::complete(1)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Loading);
+        assert_eq!(
+            app.world.resource::<ProgressCounter>().progress(),
+            Progress { done: 1, total: 3 }
+        );
+
+        app.world
+            .entity_mut(b)
+            .insert(ProgressComponent(Progress::complete(2)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[test]
+    fn tracked_system_without_counter_does_not_panic() {
+        let mut app = App::new();
+        app.add_system(work.track_progress());
+        app.insert_resource(ReadyAt(Instant::now()));
+        app.update();
+        app.update();
+    }
+
+    #[test]
+    fn progress_as_component() {
+        let mut world = World::new();
+        let entity = world.spawn(Progress { done: 1, total: 2 }).id();
+        assert_eq!(
+            world.get::<Progress>(entity),
+            Some(&Progress { done: 1, total: 2 })
+        );
+        let mut query = world.query::<&Progress>();
+        assert_eq!(
+            query.iter(&world).copied().collect::<Vec<_>>(),
+            vec![Progress { done: 1, total: 2 }]
+        );
+    }
+
+    #[test]
+    fn completion_epsilon_tolerates_one_unit_short() {
+        let one_short = Progress {
+            done: 99,
+            total: 100,
+        };
+
+        let mut app =
+            loading_app(ProgressPlugin::new(AppState::Loading).continue_to(AppState::Done));
+        app.insert_resource(Work(one_short));
+        app.update();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Loading);
+
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .with_completion_epsilon(1),
+        );
+        app.insert_resource(Work(one_short));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[test]
+    fn continue_to_lazy_transitions_to_the_computed_target() {
+        use crate::NextLoadingTarget;
+
+        fn choose(mut commands: Commands) {
+            commands.insert_resource(NextLoadingTarget(AppState::Failed));
+        }
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading).continue_to_lazy(choose));
+        app.insert_resource(Work(Progress::complete(1)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Failed);
+    }
+
+    #[test]
+    fn continue_to_lazy_transitions_when_the_target_arrives() {
+        use crate::NextLoadingTarget;
+
+        fn wait() {}
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading).continue_to_lazy(wait));
+        app.init_resource::<Fired>();
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            fire.with_run_criteria(crate::legacy::progress_just_completed::<AppState>),
+        );
+        app.insert_resource(Work(Progress::complete(1)));
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::Loading);
+        assert_eq!(app.world.resource::<Fired>().0, 0);
+
+        app.insert_resource(NextLoadingTarget(AppState::Done));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+        assert_eq!(app.world.resource::<Fired>().0, 1);
+        app.update();
+        assert_eq!(app.world.resource::<Fired>().0, 1);
+    }
+
+    #[test]
+    fn ordered_after_tracking_marker_type() {
+        /// The progress seen by a system ordered after the marker type
+        #[derive(Resource, Default)]
+        struct Seen(Vec<Progress>);
+
+        fn record(counter: Res<ProgressCounter>, mut seen: ResMut<Seen>) {
+            seen.0.push(counter.progress());
+        }
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.init_resource::<Seen>();
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading)
+                .with_system(record.after(crate::label::Tracking)),
+        );
+        app.insert_resource(Work(Progress { done: 1, total: 2 }));
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world.resource::<Seen>().0,
+            vec![Progress { done: 1, total: 2 }; 3]
+        );
+    }
+
+    #[test]
+    fn verbose_records_diagnostics_only_when_enabled() {
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading).verbose());
+        app.insert_resource(Work(Progress::none(1)));
+        app.update();
+        app.update();
+        assert_eq!(counter(&app).history().len(), 2);
+        let systems = counter(&app).systems_progress();
+        assert_eq!(systems.len(), 1);
+        assert_eq!(systems[0].1, Progress::none(1));
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.insert_resource(Work(Progress::none(1)));
+        app.update();
+        app.update();
+        assert!(counter(&app).history().is_empty());
+        assert!(counter(&app).systems_progress().is_empty());
+    }
+
+    #[cfg(feature = "scene")]
+    #[test]
+    fn scene_incomplete_until_instantiated() {
+        use bevy_asset::{AssetPlugin, Assets, Handle, HandleId};
+        use bevy_scene::{Scene, ScenePlugin, SceneSpawner};
+
+        use crate::scene::ScenesLoading;
+
+        let mut app = App::new();
+        app.add_plugin(AssetPlugin::default());
+        app.add_plugin(ScenePlugin);
+        app.add_state(AppState::Loading);
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .track_scenes()
+                .with_backend(ProgressBackend::Legacy),
+        );
+
+        // the scene asset is not there yet, so it cannot be instantiated
+        let handle: Handle<Scene> = Handle::weak(HandleId::random::<Scene>());
+        let instance = app
+            .world
+            .resource_mut::<SceneSpawner>()
+            .spawn(handle.clone_weak());
+        app.world
+            .resource_mut::<ScenesLoading>()
+            .add_instance(instance);
+        app.update();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Loading);
+        assert_eq!(counter(&app).progress(), Progress::none(1));
+
+        let mut scene_world = World::new();
+        scene_world.spawn_empty();
+        app.world
+            .resource_mut::<Assets<Scene>>()
+            .set_untracked(handle, Scene::new(scene_world));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[test]
+    fn ratio_u32() {
+        assert_eq!(Progress { done: 1, total: 4 }.ratio_u32(100), 25);
+        assert_eq!(Progress { done: 1, total: 3 }.ratio_u32(100), 33);
+        assert_eq!(Progress::default().ratio_u32(100), 100);
+        assert_eq!(Progress { done: 9, total: 4 }.ratio_u32(100), 100);
+        assert_eq!(
+            Progress {
+                done: u32::MAX,
+                total: u32::MAX
+            }
+            .ratio_u32(u32::MAX),
+            u32::MAX
+        );
+    }
+
+    /// An asset that loads from any `.blob` file, for testing asset tracking
+    #[cfg(feature = "assets")]
+    #[derive(bevy_reflect::TypeUuid)]
+    #[uuid = "5d2f6a9e-0c47-4b1e-8a3d-7f61c2b9e4a8"]
+    struct Blob;
+
+    #[cfg(feature = "assets")]
+    struct BlobLoader;
+
+    #[cfg(feature = "assets")]
+    impl bevy_asset::AssetLoader for BlobLoader {
+        fn load<'a>(
+            &'a self,
+            _bytes: &'a [u8],
+            load_context: &'a mut bevy_asset::LoadContext,
+        ) -> bevy_utils::BoxedFuture<'a, Result<(), anyhow::Error>> {
+            Box::pin(async move {
+                load_context.set_default_asset(bevy_asset::LoadedAsset::new(Blob));
+                Ok(())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["blob"]
+        }
+    }
+
+    /// An app that loads [`Blob`]s from a new folder, containing the given files
+    ///
+    /// `name` must be unique for every test, as they run in parallel.
+    #[cfg(feature = "assets")]
+    fn blob_app(name: &str, files: &[&str]) -> App {
+        use bevy_asset::{AddAsset, AssetPlugin};
+
+        bevy_tasks::IoTaskPool::init(bevy_tasks::TaskPool::new);
+        let folder =
+            std::env::temp_dir().join(format!("iyes_progress_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        for file in files {
+            std::fs::write(folder.join(file), "blob").unwrap();
+        }
+
+        let mut app = App::new();
+        app.add_plugin(AssetPlugin {
+            asset_folder: folder.to_str().unwrap().into(),
+            watch_for_changes: false,
+        });
+        app.add_asset::<Blob>();
+        app.add_asset_loader(BlobLoader);
+        app
+    }
+
+    /// Update the app until the asset server is done with the given asset
+    #[cfg(feature = "assets")]
+    fn update_until_loaded(app: &mut App, handle: &bevy_asset::HandleUntyped) {
+        use bevy_asset::{AssetServer, LoadState};
+
+        let start = Instant::now();
+        while !matches!(
+            app.world.resource::<AssetServer>().get_load_state(handle),
+            LoadState::Loaded | LoadState::Failed
+        ) {
+            assert!(start.elapsed() < Duration::from_secs(10), "never loaded");
+            app.update();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[cfg(feature = "assets")]
+    #[test]
+    fn ready_check_holds_loaded_asset() {
+        use bevy_asset::AssetServer;
+
+        use crate::asset::AssetsLoading;
+
+        /// Whether the custom check lets the asset be ready
+        #[derive(Resource)]
+        struct Uploaded(bool);
+
+        let mut app = blob_app("ready_check", &["texture.blob"]);
+        app.insert_resource(Uploaded(false));
+        app.add_state(AppState::Loading);
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .track_assets()
+                .with_backend(ProgressBackend::Legacy),
+        );
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .load_untyped("texture.blob");
+        app.world
+            .resource_mut::<AssetsLoading>()
+            .add_with_check(&handle, |_, world| world.resource::<Uploaded>().0);
+
+        update_until_loaded(&mut app, &handle);
+        app.update();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Loading);
+        assert_eq!(counter(&app).progress(), Progress::none(1));
+
+        app.world.resource_mut::<Uploaded>().0 = true;
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[test]
+    fn progress_including_mock_source() {
+        use crate::ProgressSource;
+
+        struct Mock;
+
+        impl ProgressSource for Mock {
+            fn progress(&self) -> Progress {
+                Progress { done: 3, total: 4 }
+            }
+        }
+
+        let counter = ProgressCounter::default();
+        counter.manually_track(Progress { done: 1, total: 2 });
+        assert_eq!(
+            counter.progress_including(&[&Mock]),
+            Progress { done: 4, total: 6 }
+        );
+        // only accounted into the returned value
+        assert_eq!(counter.progress(), Progress { done: 1, total: 2 });
+    }
+
+    #[test]
+    fn status_of_two_labeled_systems() {
+        use crate::ProgressStatus;
+
+        fn terrain() -> Progress {
+            Progress::none(1)
+        }
+
+        fn music() -> Progress {
+            Progress::none(1)
+        }
+
+        fn props() -> Progress {
+            Progress::complete(1)
+        }
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading)
+                .with_system(terrain.track_progress_with_status("Generating terrain"))
+                .with_system(music.track_progress_with_status("Loading music"))
+                .with_system(props.track_progress_with_status("Placing props")),
+        );
+        app.update();
+        let mut active = app.world.resource::<ProgressStatus>().active();
+        active.sort();
+        assert_eq!(active, vec!["Generating terrain", "Loading music"]);
+    }
+
+    #[test]
+    fn accumulate_across_two_entries() {
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .accumulate_across_entries(),
+        );
+        app.insert_resource(Work(Progress::complete(3)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+
+        // the second entry loads more content
+        app.insert_resource(Work(Progress::none(2)));
+        app.world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::Loading)
+            .unwrap();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Loading);
+        assert_eq!(counter(&app).progress(), Progress { done: 3, total: 5 });
+
+        app.insert_resource(Work(Progress::complete(2)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+        let accumulated = app.world.resource::<crate::AccumulatedProgress<AppState>>();
+        assert_eq!(
+            accumulated
+                .get(&AppState::Loading)
+                .map(|(visible, _)| visible),
+            Some(Progress::complete(5))
+        );
+    }
+
+    #[test]
+    fn merge_same_work() {
+        let a = Progress { done: 1, total: 2 };
+        let b = Progress {
+            done: 30,
+            total: 60,
+        };
+        assert_eq!(
+            a.merge_same_work(b),
+            Progress {
+                done: 30,
+                total: 60
+            }
+        );
+
+        let c = Progress {
+            done: 60,
+            total: 60,
+        };
+        assert_eq!(
+            Progress::none(2).merge_same_work(c),
+            Progress {
+                done: 30,
+                total: 60
+            }
+        );
+
+        assert_eq!(Progress::default().merge_same_work(a), a);
+        assert_eq!(a.merge_same_work(Progress::default()), a);
+    }
+
+    #[test]
+    fn completion_frame_is_recorded() {
+        // without a state to continue to, so the counter stays around
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.insert_resource(Work(Progress::none(1)));
+        app.update();
+        app.update();
+        assert_eq!(counter(&app).completion_frame(), None);
+
+        app.insert_resource(Work(Progress::complete(1)));
+        app.update();
+        assert_eq!(counter(&app).frame(), 3);
+        assert_eq!(counter(&app).completion_frame(), Some(3));
+        app.update();
+        assert_eq!(counter(&app).completion_frame(), Some(3));
+    }
+
+    #[test]
+    fn shrinking_total_stays_monotonic() {
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.insert_resource(Work(Progress { done: 6, total: 10 }));
+        app.update();
+        assert_eq!(
+            counter(&app).progress_monotonic(),
+            Progress { done: 6, total: 10 }
+        );
+
+        // 2/4 would be a step back from 6/10
+        app.insert_resource(Work(Progress { done: 2, total: 4 }));
+        app.update();
+        assert_eq!(
+            counter(&app).progress_monotonic(),
+            Progress { done: 3, total: 4 }
+        );
+
+        // rounding up never makes `done` exceed the new total
+        app.insert_resource(Work(Progress { done: 1, total: 2 }));
+        app.update();
+        assert_eq!(
+            counter(&app).progress_monotonic(),
+            Progress { done: 2, total: 2 }
+        );
+    }
+
+    #[test]
+    fn also_tracked_state_has_its_own_counter() {
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .also_track_state(AppState::Other)
+                .continue_to(AppState::Done),
+        );
+        app.add_system_set(
+            SystemSet::on_update(AppState::Other).with_system(report.track_progress()),
+        );
+        app.insert_resource(Work(Progress { done: 1, total: 2 }));
+        app.update();
+        assert_eq!(counter(&app).progress(), Progress { done: 1, total: 2 });
+
+        app.world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::Other)
+            .unwrap();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Other);
+        assert_eq!(counter(&app).frame(), 1);
+        assert_eq!(counter(&app).progress(), Progress { done: 1, total: 2 });
+
+        app.insert_resource(Work(Progress::complete(2)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[cfg(feature = "timer")]
+    #[test]
+    fn timer_progress_halfway() {
+        use bevy_time::{Timer, TimerMode};
+
+        use crate::timer::TimerProgressExt;
+
+        let mut timer = Timer::from_seconds(2.0, TimerMode::Once);
+        timer.tick(Duration::from_secs(1));
+        assert_eq!(
+            timer.progress(),
+            Progress {
+                done: 1000,
+                total: 2000
+            }
+        );
+        assert_eq!(timer.progress().fraction(), 0.5);
+        timer.tick(Duration::from_secs(5));
+        assert!(timer.progress().is_ready());
+
+        // repeating timers report the current cycle
+        let mut timer = Timer::from_seconds(2.0, TimerMode::Repeating);
+        timer.tick(Duration::from_secs(3));
+        assert_eq!(
+            timer.progress(),
+            Progress {
+                done: 1000,
+                total: 2000
+            }
+        );
+    }
+
+    #[test]
+    fn require_work_waits_for_real_work() {
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .require_work(),
+        );
+        // nothing to do yet
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::Loading);
+
+        app.insert_resource(Work(Progress::none(2)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Loading);
+
+        app.insert_resource(Work(Progress::complete(2)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[test]
+    fn complete_and_none() {
+        assert!(Progress::complete(5).is_ready());
+        assert!(!Progress::none(5).is_ready());
+        assert_eq!(Progress::complete(4), Progress { done: 4, total: 4 });
+        assert_eq!(Progress::none(4), Progress { done: 0, total: 4 });
+        assert_eq!(
+            crate::HiddenProgress::complete(4),
+            crate::HiddenProgress(Progress::complete(4))
+        );
+        assert_eq!(
+            crate::HiddenProgress::none(4),
+            crate::HiddenProgress(Progress::none(4))
+        );
+    }
+
+    #[cfg(feature = "assets")]
+    #[test]
+    fn large_weight_dominates() {
+        use bevy_asset::AssetServer;
+
+        use crate::asset::AssetsLoading;
+
+        let mut app = blob_app("weights", &["atlas.blob"]);
+        app.add_state(AppState::Loading);
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .track_assets()
+                .with_backend(ProgressBackend::Legacy),
+        );
+        let server = app.world.resource::<AssetServer>();
+        let atlas = server.load_untyped("atlas.blob");
+        // never requested from the asset server, so it stays pending
+        let config = server.get_handle_untyped("config.blob");
+        let mut loading = app.world.resource_mut::<AssetsLoading>();
+        loading.add_weighted(&atlas, 90);
+        loading.add_weighted(&config, 10);
+
+        update_until_loaded(&mut app, &atlas);
+        app.update();
+        assert_eq!(
+            counter(&app).progress(),
+            Progress {
+                done: 90,
+                total: 100
+            }
+        );
+        assert_eq!(app.world.resource::<AssetsLoading>().remaining(), 1);
+    }
+
+    #[test]
+    fn drain_history_empties_it() {
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading).verbose());
+        app.insert_resource(Work(Progress::none(1)));
+        for _ in 0..3 {
+            app.update();
+        }
+        let samples = app.world.resource_mut::<ProgressCounter>().drain_history();
+        assert_eq!(samples.len(), 3);
+        assert!(samples.iter().all(|s| s.visible == Progress::none(1)));
+        assert!(counter(&app).history().is_empty());
+
+        // recording goes on afterwards
+        app.update();
+        assert_eq!(counter(&app).history().len(), 1);
+    }
+
+    /// Background work, for [`stream`]
+    #[derive(Resource, Default)]
+    struct Streaming(Progress);
+
+    /// Tracked in every state, not just while loading
+    fn stream(streaming: Res<Streaming>) -> Progress {
+        streaming.0
+    }
+
+    fn soft_transition_app() -> App {
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .soft_transition(),
+        );
+        app.init_resource::<Streaming>();
+        app.add_system(stream.track_progress());
+        app.insert_resource(Work(Progress::complete(1)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+        app
+    }
+
+    #[test]
+    fn soft_transition_keeps_tracking_in_the_background() {
+        let mut app = soft_transition_app();
+        app.insert_resource(Streaming(Progress::none(2)));
+        app.update();
+        assert_eq!(counter(&app).progress(), Progress::none(2));
+
+        app.insert_resource(Streaming(Progress { done: 1, total: 2 }));
+        app.update();
+        assert_eq!(counter(&app).progress(), Progress { done: 1, total: 2 });
+
+        app.insert_resource(Streaming(Progress::complete(2)));
+        app.update();
+        assert!(!app.world.contains_resource::<ProgressCounter>());
+    }
+
+    #[test]
+    fn background_counter_replaced_by_another_state_type() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        enum OtherState {
+            Idle,
+            Loading,
+        }
+
+        fn other_work() -> Progress {
+            Progress::complete(3)
+        }
+
+        let mut app = soft_transition_app();
+        app.insert_resource(Streaming(Progress::none(1)));
+        app.update();
+        assert!(app
+            .world
+            .contains_resource::<crate::BackgroundProgress<AppState>>());
+
+        // stays in the state after completing, so it keeps using the counter
+        app.add_state(OtherState::Idle);
+        app.add_plugin(
+            ProgressPlugin::new(OtherState::Loading).with_backend(ProgressBackend::Legacy),
+        );
+        app.add_system_set(
+            SystemSet::on_update(OtherState::Loading).with_system(other_work.track_progress()),
+        );
+        app.update();
+        app.world
+            .resource_mut::<State<OtherState>>()
+            .set(OtherState::Loading)
+            .unwrap();
+        app.insert_resource(Streaming(Progress::complete(1)));
+        for _ in 0..5 {
+            app.update();
+        }
+        assert!(!app
+            .world
+            .contains_resource::<crate::BackgroundProgress<AppState>>());
+        assert_eq!(counter(&app).progress(), Progress::complete(4));
+    }
+
+    #[test]
+    fn from_str() {
+        use crate::ParseProgressError;
+
+        assert_eq!(" 3 / 10 ".parse(), Ok(Progress { done: 3, total: 10 }));
+        assert_eq!(
+            "3".parse::<Progress>(),
+            Err(ParseProgressError::MissingSlash)
+        );
+        assert!(matches!(
+            "a/10".parse::<Progress>(),
+            Err(ParseProgressError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            "3/-1".parse::<Progress>(),
+            Err(ParseProgressError::InvalidNumber(_))
+        ));
+        assert_eq!(
+            "11/10".parse::<Progress>(),
+            Err(ParseProgressError::DoneExceedsTotal {
+                done: 11,
+                total: 10
+            })
+        );
+    }
+
+    #[test]
+    fn split_adds_up_to_complete() {
+        let mut counter = ProgressCounter::default();
+        counter.manually_track(Progress { done: 1, total: 3 });
+        counter.manually_track_hidden(crate::HiddenProgress(Progress { done: 2, total: 2 }));
+        // display adjustments do not apply
+        counter.set_expected_total(10);
+        let (visible, hidden) = counter.split();
+        assert_eq!(visible, Progress { done: 1, total: 3 });
+        assert_eq!(hidden, Progress { done: 2, total: 2 });
+        assert_eq!(visible + hidden, counter.progress_complete());
+    }
+
+    #[test]
+    fn progress_at_least_gates_on_half() {
+        use crate::ProgressSystemLabel;
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.init_resource::<Fired>();
+        app.add_system(
+            fire.with_run_criteria(crate::legacy::progress_at_least(0.5))
+                .after(ProgressSystemLabel::Tracking),
+        );
+        app.insert_resource(Work(Progress { done: 1, total: 4 }));
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<Fired>().0, 0);
+
+        app.insert_resource(Work(Progress { done: 2, total: 4 }));
+        app.update();
+        assert_eq!(app.world.resource::<Fired>().0, 1);
+        app.insert_resource(Work(Progress { done: 3, total: 4 }));
+        app.update();
+        assert_eq!(app.world.resource::<Fired>().0, 2);
+    }
+
+    #[test]
+    fn completed_child_feeds_parent_counter() {
+        fn parent_work() -> Progress {
+            Progress::none(1)
+        }
+
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Other)
+                .report_completion_to(AppState::Other, 2),
+        );
+        app.add_plugin(ProgressPlugin::new(AppState::Other).with_backend(ProgressBackend::Legacy));
+        app.add_system_set(
+            SystemSet::on_update(AppState::Other).with_system(parent_work.track_progress()),
+        );
+        app.insert_resource(Work(Progress::complete(1)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Other);
+        assert_eq!(
+            app.world
+                .resource::<crate::ReportedProgress<AppState>>()
+                .get(&AppState::Other),
+            Some(Progress::complete(2))
+        );
+        app.update();
+        assert_eq!(counter(&app).progress(), Progress { done: 2, total: 3 });
+    }
+
+    #[test]
+    fn wait_handle_resolves_on_completion() {
+        use std::future::Future;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake, Waker};
+
+        /// Records whether it was woken
+        struct Flag(AtomicBool);
+
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let mut app =
+            loading_app(ProgressPlugin::new(AppState::Loading).continue_to(AppState::Done));
+        app.insert_resource(Work(Progress::none(1)));
+        app.update();
+        let mut handle = Box::pin(counter(&app).wait_handle());
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(handle.as_mut().poll(&mut cx), Poll::Pending);
+
+        app.update();
+        assert_eq!(handle.as_mut().poll(&mut cx), Poll::Pending);
+        assert!(!flag.0.load(Ordering::SeqCst));
+
+        app.insert_resource(Work(Progress::complete(1)));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert_eq!(handle.as_mut().poll(&mut cx), Poll::Ready(true));
+    }
+
+    #[cfg(feature = "assets")]
+    #[test]
+    fn remaining_decreases_to_zero() {
+        use bevy_asset::AssetServer;
+
+        use crate::asset::AssetsLoading;
+
+        let mut app = blob_app("remaining", &["a.blob", "b.blob"]);
+        app.add_state(AppState::Loading);
+        app.add_plugin(
+            ProgressPlugin::new(AppState::Loading)
+                .track_assets()
+                .with_backend(ProgressBackend::Legacy),
+        );
+        let server = app.world.resource::<AssetServer>();
+        let handles = [server.load_untyped("a.blob"), server.load_untyped("b.blob")];
+        let mut loading = app.world.resource_mut::<AssetsLoading>();
+        for handle in &handles {
+            loading.add(handle);
+        }
+        assert_eq!(loading.remaining(), 2);
+
+        let start = Instant::now();
+        let mut remaining = vec![2];
+        while remaining.last() != Some(&0) {
+            assert!(start.elapsed() < Duration::from_secs(10), "never loaded");
+            app.update();
+            remaining.push(app.world.resource::<AssetsLoading>().remaining());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(remaining.windows(2).all(|w| w[1] <= w[0]));
+        assert!(app.world.resource::<AssetsLoading>().is_ready());
+    }
+
+    #[test]
+    fn mul_div() {
+        assert_eq!(
+            Progress { done: 1, total: 2 } * 50,
+            Progress {
+                done: 50,
+                total: 100
+            }
+        );
+        assert_eq!(
+            Progress { done: 2, total: 3 } * 4,
+            Progress { done: 8, total: 12 }
+        );
+        assert_eq!(
+            Progress {
+                done: 1,
+                total: u32::MAX / 2 + 1
+            } * 2,
+            Progress {
+                done: 2,
+                total: u32::MAX
+            }
+        );
+        assert_eq!(
+            Progress { done: 5, total: 7 } / 2,
+            Progress { done: 2, total: 3 }
+        );
+    }
+
+    #[test]
+    fn complete_check_holds_until_true() {
+        /// Whether the user clicked "Continue"
+        #[derive(Resource, Default)]
+        struct Clicked(bool);
+
+        fn clicked(clicked: Res<Clicked>) -> bool {
+            clicked.0
+        }
+
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .on_complete_check(clicked),
+        );
+        app.init_resource::<Clicked>();
+        app.insert_resource(Work(Progress::complete(1)));
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::Loading);
+        assert_eq!(counter(&app).completion_frame(), None);
+
+        app.insert_resource(Clicked(true));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[test]
+    #[cfg(feature = "bevy_diagnostic")]
+    fn diagnostics_registered_and_updated() {
+        use crate::prelude::ProgressMetrics;
+        use bevy_diagnostic::Diagnostics;
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading).with_diagnostics());
+        app.init_resource::<Diagnostics>();
+        app.insert_resource(Work(Progress { done: 1, total: 2 }));
+        app.update();
+
+        let diagnostics = app.world.resource::<Diagnostics>();
+        let value = |id| diagnostics.get(id).and_then(|d| d.value());
+        assert_eq!(value(ProgressMetrics::VISIBLE), Some(50.0));
+        assert_eq!(value(ProgressMetrics::COMPLETE), Some(50.0));
+        assert_eq!(value(ProgressMetrics::SYSTEMS), Some(1.0));
+    }
+
+    #[test]
+    fn step_next() {
+        assert_eq!(Progress::step(3, 5).next(), Progress::step(4, 5));
+        assert_eq!(Progress::step(5, 5).next(), Progress::step(5, 5));
+        // never goes backwards, even if already past the total
+        assert_eq!(Progress::step(7, 5).next(), Progress::step(7, 5));
+        assert_eq!(Progress::step(u32::MAX, u32::MAX).next().done, u32::MAX);
+    }
+
+    #[test]
+    fn freeze_holds_displayed_progress() {
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.insert_resource(Work(Progress { done: 1, total: 4 }));
+        app.update();
+
+        app.insert_resource(crate::FreezeDisplayedProgress);
+        app.insert_resource(Work(Progress { done: 3, total: 4 }));
+        app.update();
+        assert_eq!(counter(&app).progress(), Progress { done: 1, total: 4 });
+        assert_eq!(
+            counter(&app).progress_complete(),
+            Progress { done: 3, total: 4 }
+        );
+    }
+
+    #[test]
+    fn expected_total_keeps_denominator() {
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading).with_expected_total(100));
+        app.insert_resource(Work(Progress {
+            done: 30,
+            total: 60,
+        }));
+        app.update();
+        assert_eq!(
+            counter(&app).progress(),
+            Progress {
+                done: 30,
+                total: 100
+            }
+        );
+        assert_eq!(counter(&app).reported_total(), 60);
+        assert_eq!(counter(&app).expected_total(), 100);
+    }
+
+    #[test]
+    #[cfg(not(feature = "iyes_loopless"))]
+    fn macro_matches_manual_registration() {
+        fn app_with(register: fn(&mut App)) -> App {
+            let mut app = App::new();
+            app.add_state(AppState::Loading);
+            app.init_resource::<Work>();
+            register(&mut app);
+            app.add_system_set(
+                SystemSet::on_update(AppState::Loading).with_system(report.track_progress()),
+            );
+            app
+        }
+
+        let mut with_macro =
+            app_with(|app| crate::progress_states!(app, AppState { Loading -> Done }));
+        let mut manual = app_with(|app| {
+            app.add_plugin(ProgressPlugin::new(AppState::Loading).continue_to(AppState::Done));
+        });
+
+        for app in [&mut with_macro, &mut manual] {
+            app.update();
+            assert_eq!(current_state(app), AppState::Loading);
+            app.insert_resource(Work(Progress::complete(1)));
+            app.update();
+            assert_eq!(current_state(app), AppState::Done);
+        }
+    }
+
+    #[test]
+    fn flag_weights() {
+        assert_eq!(Progress::flag(true, 5), Progress { done: 5, total: 5 });
+        assert_eq!(Progress::flag(false, 5), Progress { done: 0, total: 5 });
+        assert_eq!(Progress::from(true), Progress::flag(true, 1));
+    }
+
+    #[test]
+    fn secondary_completion_fires_before_transition() {
+        use crate::{HiddenProgress, ProgressSubset, SecondaryProgressComplete};
+
+        /// Whether the hidden work is done
+        #[derive(Resource, Default)]
+        struct HiddenDone(bool);
+
+        fn hidden(done: Res<HiddenDone>) -> HiddenProgress {
+            HiddenProgress(Progress::flag(done.0, 1))
+        }
+
+        fn secondary_events(app: &App) -> usize {
+            let events = app
+                .world
+                .resource::<Events<SecondaryProgressComplete<AppState>>>();
+            events.get_reader().iter(events).count()
+        }
+
+        let mut app = loading_app(
+            ProgressPlugin::new(AppState::Loading)
+                .continue_to(AppState::Done)
+                .secondary_completion(ProgressSubset::Visible),
+        );
+        app.init_resource::<HiddenDone>();
+        app.add_system_set(
+            SystemSet::on_update(AppState::Loading).with_system(hidden.track_progress()),
+        );
+        app.insert_resource(Work(Progress::complete(1)));
+        app.update();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Loading);
+        // only sent once, not on every frame
+        assert_eq!(secondary_events(&app), 1);
+
+        app.insert_resource(HiddenDone(true));
+        app.update();
+        assert_eq!(current_state(&app), AppState::Done);
+    }
+
+    #[test]
+    fn observer_throttle_limits_calls() {
+        use crate::prelude::ObserverThrottle;
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        fn observed_calls(throttle: ObserverThrottle) -> u32 {
+            let calls = Arc::new(AtomicU32::new(0));
+            let mut counter = ProgressCounter::default();
+            counter.set_observer_throttle(throttle);
+            let c = calls.clone();
+            counter.add_observer(move |_| {
+                c.fetch_add(1, Ordering::Relaxed);
+            });
+            // a full load in tiny increments
+            for i in 0..=100 {
+                counter.set_total(100);
+                counter.set_done(i);
+                counter.notify_observers(0);
+            }
+            calls.load(Ordering::Relaxed)
+        }
+
+        assert_eq!(observed_calls(ObserverThrottle::default()), 101);
+        let throttled = observed_calls(ObserverThrottle::change(0.1));
+        // the start, about every 10%, and the end
+        assert!((2..=12).contains(&throttled), "{} calls", throttled);
+    }
+
+    #[test]
+    fn driven_value_tracks_fraction() {
+        use crate::prelude::{drive_progress_values, ProgressDrivenValue, ProgressValueSource};
+        use crate::ProgressSystemLabel;
+
+        #[derive(Component)]
+        struct Volume(f32);
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.add_system(drive_progress_values::<Volume>.after(ProgressSystemLabel::Tracking));
+        let entity = app
+            .world
+            .spawn((
+                Volume(0.0),
+                ProgressDrivenValue::new(ProgressValueSource::Visible, |v: &mut Volume, x| v.0 = x),
+            ))
+            .id();
+
+        app.insert_resource(Work(Progress { done: 1, total: 4 }));
+        app.update();
+        assert_eq!(app.world.get::<Volume>(entity).unwrap().0, 0.25);
+
+        app.insert_resource(Work(Progress { done: 1, total: 2 }));
+        app.update();
+        assert_eq!(app.world.get::<Volume>(entity).unwrap().0, 0.5);
+    }
+
+    #[test]
+    fn finalized_after_check() {
+        use crate::{ProgressFinalized, ProgressSystemLabel};
+
+        /// What `ProgressFinalized` said, before tracking and in `PostUpdate`
+        #[derive(Resource, Default)]
+        struct Seen {
+            early: Option<bool>,
+            late: Option<bool>,
+        }
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.init_resource::<Seen>();
+        app.add_system(
+            (|finalized: Res<ProgressFinalized>, mut seen: ResMut<Seen>| {
+                seen.early = Some(finalized.is_finalized());
+            })
+            .after(ProgressSystemLabel::Preparation)
+            .before(ProgressSystemLabel::Tracking),
+        );
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            |finalized: Res<ProgressFinalized>, mut seen: ResMut<Seen>| {
+                seen.late = Some(finalized.is_finalized());
+            },
+        );
+        app.insert_resource(Work(Progress { done: 1, total: 2 }));
+        app.update();
+        app.update();
+
+        let seen = app.world.resource::<Seen>();
+        assert_eq!(seen.early, Some(false));
+        assert_eq!(seen.late, Some(true));
+    }
+
+    #[test]
+    fn active_states_listed_while_entered() {
+        use crate::ActiveProgressStates;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        enum OtherState {
+            Loading,
+        }
+
+        let mut app = loading_app(ProgressPlugin::new(AppState::Loading));
+        app.add_state(OtherState::Loading);
+        app.add_plugin(
+            ProgressPlugin::new(OtherState::Loading).with_backend(ProgressBackend::Legacy),
+        );
+        app.update();
+        let active = app.world.resource::<ActiveProgressStates>();
+        assert_eq!(active.len(), 2);
+        assert!(active.get(&AppState::Loading).is_some());
+        assert!(active.get(&OtherState::Loading).is_some());
+
+        app.world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::Done)
+            .unwrap();
+        app.update();
+        let active = app.world.resource::<ActiveProgressStates>();
+        assert_eq!(active.len(), 1);
+        assert!(active.get(&AppState::Loading).is_none());
+        assert!(active.get(&OtherState::Loading).is_some());
+    }
 }
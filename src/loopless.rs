@@ -1,22 +1,76 @@
+//! Backend using `iyes_loopless` states
+//!
+//! This is the default if the `iyes_loopless` cargo feature is enabled.
+
+use std::borrow::Cow;
+
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_ecs::schedule::StateData;
 
-use crate::{ProgressPlugin, ProgressSystemLabel};
-use crate::ProgressCounter;
+use crate::{ProgressCompletion, ProgressCounter, ProgressPlugin, ProgressSystemLabel};
+use crate::TransitionConfig;
 use crate::ApplyProgress;
 
+/// Most used imports from this backend
 pub mod prelude {
+    pub use super::progress_at_least;
+    pub use super::progress_just_completed;
     pub use super::ProgressSystem;
 }
 
-impl<S: StateData> Plugin for ProgressPlugin<S> {
-    fn build(&self, app: &mut App) {
+impl<S: StateData> ProgressPlugin<S> {
+    pub(crate) fn build_loopless(&self, app: &mut App) {
+        self.build_common(app);
+        let transition_config = self.transition_config(&mut app.world);
+
+        #[cfg(feature = "assets")]
+        if self.track_assets {
+            app.init_resource::<crate::asset::AssetsLoading>();
+        }
+
+        #[cfg(not(feature = "assets"))]
+        if self.track_assets {
+            panic!("Enable the \"assets\" cargo feature to use assets tracking!");
+        }
+
+        #[cfg(feature = "scene")]
+        if self.track_scenes {
+            app.init_resource::<crate::scene::ScenesLoading>();
+        }
+
+        #[cfg(not(feature = "scene"))]
+        if self.track_scenes {
+            panic!("Enable the \"scene\" cargo feature to use scene tracking!");
+        }
+
+        for state in self.tracked_states() {
+            self.build_for_state_loopless(app, state, transition_config.clone());
+        }
+    }
+
+    fn build_for_state_loopless(
+        &self,
+        app: &mut App,
+        state: S,
+        transition_config: TransitionConfig<S>,
+    ) {
         use iyes_loopless::condition::IntoConditionalExclusiveSystem;
         use iyes_loopless::prelude::*;
 
-        app.add_enter_system(self.state.clone(), crate::loadstate_enter);
-        app.add_exit_system(self.state.clone(), crate::loadstate_exit);
+        app.add_enter_system(
+            state.clone(),
+            crate::loadstate_enter(
+                state.clone(),
+                self.expected_total,
+                self.observer_throttle,
+                self.diagnostics.clone(),
+            ),
+        );
+        app.add_exit_system(
+            state.clone(),
+            crate::loadstate_exit(state.clone(), self.accumulate, self.soft_transition),
+        );
 
         #[derive(Debug, Clone)]
         struct StageLabel(String);
@@ -27,7 +81,7 @@ impl<S: StateData> Plugin for ProgressPlugin<S> {
             }
         }
 
-        let stagelabel = StageLabel(format!("iyes_progress init: {:?}", &self.state));
+        let stagelabel = StageLabel(format!("iyes_progress init: {:?}", &state));
 
         app.add_stage_after(
             iyes_loopless::state::StateTransitionStageLabel::from_type::<S>(),
@@ -36,35 +90,88 @@ impl<S: StateData> Plugin for ProgressPlugin<S> {
         );
 
         app.add_system_to_stage(
-            stagelabel,
+            stagelabel.clone(),
             crate::next_frame
-                .run_in_state(self.state.clone())
+                .run_in_state(state.clone())
                 .at_start()
-                .label(ProgressSystemLabel::Preparation),
+                .label(ProgressSystemLabel::Preparation)
+                .label(crate::label::Preparation),
         );
 
+        if self.soft_transition {
+            // runs after the transition, so it also resets the counter on the frame of the exit
+            app.add_system_to_stage(
+                stagelabel,
+                crate::background_next_frame(state.clone()).at_start(),
+            );
+            app.add_system_to_stage(
+                CoreStage::Last,
+                crate::background_check(state.clone(), self.completion_epsilon),
+            );
+        }
+
         app.add_system_to_stage(
             CoreStage::Last,
-            check_progress::<S>(self.next_state.clone())
-                .run_in_state(self.state.clone())
+            check_progress::<S>(transition_config)
+                .run_in_state(state.clone())
                 .at_end()
-                .label(ProgressSystemLabel::CheckProgress),
+                .label(ProgressSystemLabel::CheckProgress)
+                .label(crate::label::CheckProgress),
         );
 
+        if self.track_entities {
+            app.add_system(
+                crate::entity_progress
+                    .track_progress()
+                    .run_in_state(state.clone()),
+            );
+        }
+
+        if self.diagnostics.is_some() {
+            app.add_system_to_stage(
+                CoreStage::Last,
+                crate::diagnostics::progress_diagnostics
+                    .run_in_state(state.clone())
+                    .after(ProgressSystemLabel::Tracking),
+            );
+        }
+
+        #[cfg(feature = "bevy_diagnostic")]
+        if self.bevy_diagnostics {
+            app.add_system_to_stage(
+                CoreStage::Last,
+                crate::metrics::update_metrics
+                    .run_in_state(state.clone())
+                    .after(ProgressSystemLabel::Tracking),
+            );
+        }
+
         #[cfg(feature = "assets")]
         if self.track_assets {
-            app.init_resource::<crate::asset::AssetsLoading>();
-            app.add_exit_system(self.state.clone(), crate::asset::assets_loading_reset);
+            app.add_exit_system(state.clone(), crate::asset::assets_loading_reset);
             app.add_system(
                 crate::asset::assets_progress
-                    .track_progress()
-                    .run_in_state(self.state.clone()),
+                    .chain(crate::apply_tracked_progress("AssetsLoading", None))
+                    .into_conditional()
+                    .label(ProgressSystemLabel::Tracking)
+                    .label(crate::label::Tracking)
+                    .before(crate::UserTracking)
+                    .run_in_state(state.clone()),
             );
         }
 
-        #[cfg(not(feature = "assets"))]
-        if self.track_assets {
-            panic!("Enable the \"assets\" cargo feature to use assets tracking!");
+        #[cfg(feature = "scene")]
+        if self.track_scenes {
+            app.add_exit_system(state.clone(), crate::scene::scenes_loading_reset);
+            app.add_system(
+                crate::scene::scenes_progress
+                    .chain(crate::apply_tracked_progress("ScenesLoading", None))
+                    .into_conditional()
+                    .label(ProgressSystemLabel::Tracking)
+                    .label(crate::label::Tracking)
+                    .before(crate::UserTracking)
+                    .run_in_state(state.clone()),
+            );
         }
     }
 }
@@ -74,7 +181,20 @@ pub trait ProgressSystem<Params, T: ApplyProgress>: IntoSystem<(), T, Params> {
     /// Call this to add your system returning [`Progress`] to your [`App`]
     ///
     /// This adds the functionality for tracking the returned Progress.
+    ///
+    /// If the system runs while there is no [`ProgressCounter`](crate::ProgressCounter)
+    /// resource, the returned value is ignored (and a warning is logged, once).
     fn track_progress(self) -> iyes_loopless::condition::ConditionalSystemDescriptor;
+
+    /// Like `track_progress`, but also report a human-readable status text
+    ///
+    /// As long as the system's progress is not complete, the text is reported
+    /// in the [`ProgressStatus`](crate::ProgressStatus) resource, so it can be
+    /// shown in the UI (like "Generating terrain...").
+    fn track_progress_with_status(
+        self,
+        status: impl Into<Cow<'static, str>>,
+    ) -> iyes_loopless::condition::ConditionalSystemDescriptor;
 }
 
 impl<S, T, Params> ProgressSystem<Params, T> for S
@@ -84,23 +204,69 @@ where
 {
     fn track_progress(self) -> iyes_loopless::condition::ConditionalSystemDescriptor {
         use iyes_loopless::condition::IntoConditionalSystem;
-        self.chain(
-            |In(progress): In<T>, counter: Res<ProgressCounter>| {
-                progress.apply_progress(&*counter);
-            },
-        )
+        self.chain(crate::apply_tracked_progress::<T>(
+            std::any::type_name::<S>(),
+            None,
+        ))
+        .into_conditional()
+        .label(ProgressSystemLabel::Tracking)
+        .label(crate::label::Tracking)
+        .label(crate::UserTracking)
+    }
+
+    fn track_progress_with_status(
+        self,
+        status: impl Into<Cow<'static, str>>,
+    ) -> iyes_loopless::condition::ConditionalSystemDescriptor {
+        use iyes_loopless::condition::IntoConditionalSystem;
+        self.chain(crate::apply_tracked_progress::<T>(
+            std::any::type_name::<S>(),
+            Some(status.into()),
+        ))
         .into_conditional()
         .label(ProgressSystemLabel::Tracking)
+        .label(crate::label::Tracking)
+        .label(crate::UserTracking)
     }
 }
 
-fn check_progress<S: StateData>(next_state: Option<S>) -> impl FnMut(&mut World) {
+/// Run condition that is true once the visible progress reaches the given fraction
+///
+/// For example, `progress_at_least(0.5)` is true as soon as loading is half done
+/// (as returned by [`ProgressCounter::progress`](crate::ProgressCounter::progress)).
+///
+/// It is false whenever there is no [`ProgressCounter`](crate::ProgressCounter)
+/// (such as outside of the loading state), or no work has been reported yet
+/// (a total of `0`). You should order your system after
+/// [`ProgressSystemLabel::Tracking`], so that it sees the progress reported on
+/// the current frame.
+///
+/// Use it like `.run_if(progress_at_least(0.5))`.
+pub fn progress_at_least(
+    fraction: f32,
+) -> impl FnMut(Option<Res<ProgressCounter>>) -> bool + Clone {
+    move |counter| crate::progress_reached(counter.as_deref(), fraction)
+}
+
+/// Run condition that is true only on the frame when progress in state `S` completes
+///
+/// See [`ProgressCompletion::is_transitioning`](crate::ProgressCompletion::is_transitioning)
+/// for details on exactly which frame that is.
+///
+/// Useful for running one-shot logic: `.run_if(progress_just_completed::<MyState>)`.
+pub fn progress_just_completed<S: StateData>(
+    completion: Option<Res<ProgressCompletion<S>>>,
+) -> bool {
+    crate::just_completed(completion.as_deref())
+}
+
+/// How many frames after the check-progress system the state transition happens
+const TRANSITION_DELAY: u64 = 1;
+
+fn check_progress<S: StateData>(mut config: TransitionConfig<S>) -> impl FnMut(&mut World) {
     move |world| {
-        let progress = world.resource::<ProgressCounter>().progress_complete();
-        if progress.is_ready() {
-            if let Some(next_state) = &next_state {
-                world.insert_resource(iyes_loopless::state::NextState(next_state.clone()));
-            }
+        if let Some(next_state) = config.next_state(world, TRANSITION_DELAY) {
+            world.insert_resource(iyes_loopless::state::NextState(next_state));
         }
     }
 }
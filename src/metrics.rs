@@ -0,0 +1,55 @@
+use std::sync::atomic::Ordering as MemOrdering;
+
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::prelude::*;
+
+use crate::ProgressCounter;
+
+/// Ids of the diagnostics registered with `bevy_diagnostic`
+///
+/// See [`ProgressPlugin::with_diagnostics`](crate::ProgressPlugin::with_diagnostics).
+/// The values are only updated while in a loading state.
+pub struct ProgressMetrics;
+
+impl ProgressMetrics {
+    /// The visible progress (as returned by `ProgressCounter::progress`), in percent
+    pub const VISIBLE: DiagnosticId =
+        DiagnosticId::from_u128(0x6a0b_2c1e_8f4d_4a57_9e3c_51d7_0b2e_a401);
+    /// All progress, including hidden (as returned by `ProgressCounter::progress_complete`), in percent
+    pub const COMPLETE: DiagnosticId =
+        DiagnosticId::from_u128(0x6a0b_2c1e_8f4d_4a57_9e3c_51d7_0b2e_a402);
+    /// The number of tracked systems that reported progress on the last frame
+    pub const SYSTEMS: DiagnosticId =
+        DiagnosticId::from_u128(0x6a0b_2c1e_8f4d_4a57_9e3c_51d7_0b2e_a403);
+}
+
+pub(crate) fn setup_metrics(diagnostics: Option<ResMut<Diagnostics>>) {
+    if let Some(mut diagnostics) = diagnostics {
+        diagnostics.add(Diagnostic::new(ProgressMetrics::VISIBLE, "progress", 20).with_suffix("%"));
+        diagnostics.add(
+            Diagnostic::new(ProgressMetrics::COMPLETE, "progress_complete", 20).with_suffix("%"),
+        );
+        diagnostics.add(Diagnostic::new(
+            ProgressMetrics::SYSTEMS,
+            "progress_systems",
+            20,
+        ));
+    }
+}
+
+pub(crate) fn update_metrics(
+    diagnostics: Option<ResMut<Diagnostics>>,
+    counter: Option<Res<ProgressCounter>>,
+) {
+    if let (Some(mut diagnostics), Some(counter)) = (diagnostics, counter) {
+        diagnostics.add_measurement(ProgressMetrics::VISIBLE, || {
+            counter.progress().fraction() as f64 * 100.0
+        });
+        diagnostics.add_measurement(ProgressMetrics::COMPLETE, || {
+            counter.progress_complete().fraction() as f64 * 100.0
+        });
+        diagnostics.add_measurement(ProgressMetrics::SYSTEMS, || {
+            counter.systems_tracked.load(MemOrdering::Acquire) as f64
+        });
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use crate::Progress;
+
+/// Callback to be notified when progress changes
+///
+/// See [`ProgressCounter::add_observer`](crate::ProgressCounter::add_observer).
+pub(crate) type Observer = Box<dyn FnMut(Progress) + Send>;
+
+/// Limits on how often progress observers are called
+///
+/// See [`ProgressCounter::set_observer_throttle`](crate::ProgressCounter::set_observer_throttle).
+///
+/// Observers are only ever called when the visible progress has changed. With
+/// a throttle, they are only called if, in addition, all of the configured
+/// limits are satisfied, relative to the last time they were called. The first
+/// value (when the counter is first checked after entering the state) and any
+/// value where the work is complete (within the completion epsilon, see
+/// [`ProgressPlugin::with_completion_epsilon`](crate::ProgressPlugin::with_completion_epsilon))
+/// are always reported, regardless of the throttle, so that observers do not
+/// miss the start and the end.
+///
+/// The default value does not throttle at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ObserverThrottle {
+    /// Minimum time between calls
+    pub min_interval: Option<Duration>,
+    /// Minimum change of the completed fraction (0.0..1.0) between calls
+    ///
+    /// For example, `0.1` means at most once per 10% of progress.
+    pub min_change: Option<f32>,
+}
+
+impl ObserverThrottle {
+    /// Throttle to at most one call per `interval`
+    pub fn interval(interval: Duration) -> Self {
+        ObserverThrottle {
+            min_interval: Some(interval),
+            min_change: None,
+        }
+    }
+
+    /// Throttle to at most one call per `change` of the completed fraction
+    pub fn change(change: f32) -> Self {
+        ObserverThrottle {
+            min_interval: None,
+            min_change: Some(change),
+        }
+    }
+
+    /// Is it ok to call observers with `current`, when they were last called with
+    /// `last`, `since` ago?
+    pub(crate) fn allows(&self, last: Progress, current: Progress, since: Duration) -> bool {
+        let interval_ok = self.min_interval.is_none_or(|min| since >= min);
+        let change_ok = self
+            .min_change
+            .is_none_or(|min| (current.fraction() - last.fraction()).abs() >= min);
+        interval_ok && change_ok
+    }
+}
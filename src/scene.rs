@@ -0,0 +1,91 @@
+use bevy_asset::prelude::*;
+use bevy_asset::LoadState;
+use bevy_ecs::prelude::*;
+use bevy_scene::{DynamicScene, InstanceId, Scene, SceneInstance, SceneSpawner};
+use bevy_utils::HashSet;
+
+use crate::Progress;
+
+/// Resource for tracking the spawning of scenes
+///
+/// Spawning a scene is asynchronous: the scene asset needs to be loaded, and
+/// then its entities are instantiated over one or more frames. If you want to
+/// wait for that to complete before transitioning to the next app state, add
+/// your scenes here.
+///
+/// If you spawn scenes using `SceneSpawner` directly, add the returned
+/// `InstanceId` with `.add_instance(id)`. If you spawn them using a
+/// `SceneBundle`, add the entity with `.add_entity(entity)`.
+///
+/// Note that entities that have been despawned, or whose scene asset failed
+/// to load, are counted as completed. Otherwise, your game could get stuck on
+/// the loading screen.
+///
+/// Just like [`AssetsLoading`](crate::prelude::AssetsLoading), this resource
+/// is initialized with the app, and simply cleared/reset on exiting the load
+/// state.
+#[derive(Default, Resource)]
+pub struct ScenesLoading {
+    instances: HashSet<InstanceId>,
+    entities: HashSet<Entity>,
+    total: u32,
+}
+
+impl ScenesLoading {
+    /// Add a scene instance (as returned by `SceneSpawner`) to be tracked
+    pub fn add_instance(&mut self, instance: InstanceId) {
+        self.instances.insert(instance);
+        self.total += 1;
+    }
+
+    /// Add an entity with a `SceneBundle` to be tracked
+    pub fn add_entity(&mut self, entity: Entity) {
+        self.entities.insert(entity);
+        self.total += 1;
+    }
+
+    /// Have all scenes finished spawning?
+    pub fn is_ready(&self) -> bool {
+        self.instances.is_empty() && self.entities.is_empty()
+    }
+}
+
+pub(crate) fn scenes_progress(
+    mut loading: ResMut<ScenesLoading>,
+    spawner: Res<SceneSpawner>,
+    server: Res<AssetServer>,
+    q_instance: Query<(
+        Option<&SceneInstance>,
+        Option<&Handle<Scene>>,
+        Option<&Handle<DynamicScene>>,
+    )>,
+) -> Progress {
+    loading
+        .instances
+        .retain(|instance| !spawner.instance_is_ready(*instance));
+    loading
+        .entities
+        .retain(|entity| match q_instance.get(*entity) {
+            // `SceneInstance` is only inserted once the scene starts spawning
+            Ok((Some(instance), _, _)) => !spawner.instance_is_ready(**instance),
+            // if the scene asset failed to load, it never starts spawning
+            Ok((None, scene, dynamic_scene)) => {
+                let load_state = scene
+                    .map(|handle| server.get_load_state(handle))
+                    .or_else(|| dynamic_scene.map(|handle| server.get_load_state(handle)));
+                load_state != Some(LoadState::Failed)
+            }
+            // despawned
+            Err(_) => false,
+        });
+
+    let pending = (loading.instances.len() + loading.entities.len()) as u32;
+    Progress {
+        done: loading.total - pending,
+        total: loading.total,
+    }
+}
+
+pub(crate) fn scenes_loading_reset(mut loading: ResMut<ScenesLoading>) {
+    *loading = ScenesLoading::default();
+}
@@ -0,0 +1,55 @@
+use bevy_time::{Timer, TimerMode};
+
+use crate::{ApplyProgress, Progress, ProgressCounter};
+
+/// Progress of a Bevy [`Timer`]
+///
+/// Bridges timers into the progress model of this crate, for things like
+/// cooldowns or animations. The values are in milliseconds: `done` is the
+/// elapsed time and `total` is the duration of the timer.
+///
+/// For repeating timers, the progress of the current cycle is reported.
+///
+/// It can be returned from tracked systems, or converted into a [`Progress`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimerProgress(pub Progress);
+
+impl From<&Timer> for TimerProgress {
+    fn from(timer: &Timer) -> TimerProgress {
+        let total = timer.duration().as_millis() as u32;
+        let done = if timer.finished() && timer.mode() == TimerMode::Once {
+            total
+        } else {
+            timer.elapsed().as_millis() as u32
+        };
+        TimerProgress(Progress { done, total })
+    }
+}
+
+impl From<TimerProgress> for Progress {
+    fn from(p: TimerProgress) -> Progress {
+        p.0
+    }
+}
+
+impl ApplyProgress for TimerProgress {
+    fn apply_progress(self, total: &ProgressCounter) {
+        total.manually_track(self.0);
+    }
+
+    fn split(self) -> (Progress, Progress) {
+        (self.0, Progress::default())
+    }
+}
+
+/// Extension trait to get the [`Progress`] of a Bevy [`Timer`]
+pub trait TimerProgressExt {
+    /// Get the progress of the timer (see [`TimerProgress`])
+    fn progress(&self) -> Progress;
+}
+
+impl TimerProgressExt for Timer {
+    fn progress(&self) -> Progress {
+        TimerProgress::from(self).0
+    }
+}